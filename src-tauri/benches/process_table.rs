@@ -0,0 +1,75 @@
+//! Compares resolving socket owners through the cached [`ProcessTable`]
+//! with rebuilding a full sysinfo table on every sample, as the poller used
+//! to, and the same for the whole-table walk behind tree kills and
+//! `free_port`'s audit. Run with
+//! `cargo bench --no-default-features --bench process_table`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use netstat_cat::netstat;
+use netstat_cat::process_table::ProcessTable;
+use sysinfo::{ProcessesToUpdate, System};
+
+const ROUNDS: usize = 50;
+
+fn median(mut f: impl FnMut()) -> Duration {
+    let mut samples: Vec<Duration> = (0..ROUNDS)
+        .map(|_| {
+            let started = Instant::now();
+            f();
+            started.elapsed()
+        })
+        .collect();
+    samples.sort();
+    samples[ROUNDS / 2]
+}
+
+fn main() {
+    let sockets = netstat::default_source()
+        .sockets()
+        .expect("socket enumeration failed");
+    let pids: Vec<u32> = sockets
+        .iter()
+        .flat_map(|s| s.pids.iter().copied())
+        .collect();
+
+    let full = median(|| {
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        let names: HashMap<u32, String> = sys
+            .processes()
+            .iter()
+            .map(|(pid, p)| (pid.as_u32(), p.name().to_string_lossy().into_owned()))
+            .collect();
+        black_box(names);
+    });
+    let cold = median(|| {
+        black_box(ProcessTable::default().resolve(&pids));
+    });
+    let table = ProcessTable::default();
+    let warm = median(|| {
+        black_box(table.resolve(&pids));
+    });
+
+    let all = median(|| {
+        black_box(table.all());
+    });
+    let alive = median(|| {
+        black_box(table.is_alive(std::process::id()));
+    });
+
+    let stats = table.stats();
+    println!(
+        "{} sockets, {} owning PIDs ({} resolved), median of {ROUNDS} rounds",
+        sockets.len(),
+        stats.requested,
+        stats.resolved
+    );
+    println!("full System refresh   {full:>12?}");
+    println!("ProcessTable (cold)   {cold:>12?}");
+    println!("ProcessTable (cached) {warm:>12?}");
+    println!("ProcessTable::all     {all:>12?}");
+    println!("ProcessTable::is_alive {alive:>11?}");
+}
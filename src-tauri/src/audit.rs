@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::batch_kill::KillTarget;
+use crate::error::NetstatCatError;
+use crate::process_control::{KillReport, Signal};
+use crate::process_table::ProcessTable;
+use crate::tracker;
+
+/// Emitted with a [`NetstatCatError`] when entries cannot be appended to the
+/// audit log.
+pub const ERROR_EVENT: &str = "audit-error";
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    pub pid: u32,
+    pub name: String,
+    /// The signal asked for; a `SIGKILL` escalation shows in `result`.
+    pub signal: Signal,
+    pub user: Option<String>,
+    /// `exited`, `killed` (after escalating to `SIGKILL`), `running`,
+    /// `refused` (protected) or `failed`.
+    pub result: &'static str,
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    fn new(
+        pid: u32,
+        name: String,
+        user: Option<String>,
+        signal: Signal,
+        report: Option<&KillReport>,
+        error: Option<&NetstatCatError>,
+    ) -> Self {
+        let result = match (report, error) {
+            (_, Some(NetstatCatError::Protected { .. })) => "refused",
+            (_, Some(_)) => "failed",
+            (Some(r), None) if r.exited && r.sent.len() > 1 => "killed",
+            (Some(r), None) if r.exited => "exited",
+            _ => "running",
+        };
+        AuditEntry {
+            timestamp: tracker::now_ms(),
+            pid,
+            name,
+            signal,
+            user,
+            result,
+            error: error.map(|e| e.to_string()),
+        }
+    }
+
+    pub fn from_target(target: &KillTarget, signal: Signal) -> Self {
+        AuditEntry::new(
+            target.pid,
+            target.name.clone(),
+            target.user.clone(),
+            signal,
+            target.report.as_ref(),
+            target.error.as_ref(),
+        )
+    }
+}
+
+/// Names and owners of processes, captured before a kill so the log can
+/// still name the processes that are gone afterwards.
+pub struct Owners {
+    processes: HashMap<u32, (String, Option<String>)>,
+}
+
+impl Owners {
+    /// Just `pids`, through the shared process table.
+    pub fn resolve(table: &ProcessTable, pids: &[u32]) -> Self {
+        let processes = table
+            .lookup(pids)
+            .into_iter()
+            .map(|(pid, entry)| (pid, (entry.name, entry.user)))
+            .collect();
+        Owners { processes }
+    }
+
+    /// Every process, for operations that find their targets as they go.
+    pub fn capture(table: &ProcessTable) -> Self {
+        let processes = table
+            .all()
+            .into_iter()
+            .map(|(pid, entry)| (pid, (entry.name, entry.user)))
+            .collect();
+        Owners { processes }
+    }
+
+    pub fn entry(
+        &self,
+        pid: u32,
+        signal: Signal,
+        report: Option<&KillReport>,
+        error: Option<&NetstatCatError>,
+    ) -> AuditEntry {
+        let (name, user) = self.processes.get(&pid).cloned().unwrap_or_default();
+        AuditEntry::new(pid, name, user, signal, report, error)
+    }
+}
+
+/// Append-only log of executed kills, one JSON object per line.
+pub struct AuditLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        AuditLog {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn record(&self, entries: &[AuditEntry]) -> Result<(), NetstatCatError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry).expect("audit entry serializes"));
+            lines.push('\n');
+        }
+
+        let _lock = self.lock.lock().unwrap();
+        let io_err = |e: std::io::Error| NetstatCatError::Io {
+            message: format!("Failed to write {}: {e}", self.path.display()),
+            errno: e.raw_os_error(),
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        // One write per batch so concurrent commands never interleave lines.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(io_err)
+    }
+}
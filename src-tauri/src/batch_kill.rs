@@ -0,0 +1,99 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use crate::error::NetstatCatError;
+use crate::netstat;
+use crate::process_control::{self, KillOptions, KillReport};
+use crate::process_table::ProcessTable;
+use crate::protection::Guard;
+
+/// One process of a batch kill, described before anything is sent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillTarget {
+    pub pid: u32,
+    pub name: String,
+    /// Owner's user name, or the numeric UID if it has no name.
+    pub user: Option<String>,
+    /// Local ports of the sockets it holds, ascending.
+    pub ports: Vec<u16>,
+    /// `None` for a dry run.
+    pub report: Option<KillReport>,
+    /// Why it was (or would be) left alone, or why signalling failed.
+    pub error: Option<NetstatCatError>,
+}
+
+/// Name, owner and ports of each PID, in the given order without
+/// duplicates. PIDs that do not exist get a `ProcessNotFound` error.
+pub fn describe(pids: &[u32], table: &ProcessTable) -> Result<Vec<KillTarget>, NetstatCatError> {
+    let mut seen = HashSet::new();
+    let pids: Vec<u32> = pids
+        .iter()
+        .copied()
+        .filter(|&pid| seen.insert(pid))
+        .collect();
+
+    let rows = netstat::fetch_process_info_list(table)?;
+    let mut processes = table.lookup(&pids);
+
+    Ok(pids
+        .into_iter()
+        .map(|pid| {
+            let ports: BTreeSet<u16> = rows
+                .iter()
+                .filter(|row| row.pids.contains(&pid))
+                .filter_map(|row| row.local.port)
+                .collect();
+            let mut target = KillTarget {
+                pid,
+                name: String::new(),
+                user: None,
+                ports: ports.into_iter().collect(),
+                report: None,
+                error: None,
+            };
+            match processes.remove(&pid) {
+                Some(process) => {
+                    target.name = process.name;
+                    target.user = process.user;
+                }
+                None => target.error = Some(NetstatCatError::ProcessNotFound { pid }),
+            }
+            target
+        })
+        .collect())
+}
+
+/// Signals every PID like [`process_control::kill_all`]. With `dry_run`
+/// nothing is sent; the targets only show what would be signalled and
+/// which PIDs the `guard` would refuse.
+pub fn kill_processes(
+    pids: &[u32],
+    options: KillOptions,
+    dry_run: bool,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Result<Vec<KillTarget>, NetstatCatError> {
+    let mut targets = describe(pids, table)?;
+    if let Some(guard) = guard {
+        for target in targets.iter_mut().filter(|t| t.error.is_none()) {
+            target.error = guard.check(target.pid).err();
+        }
+    }
+    if dry_run {
+        return Ok(targets);
+    }
+
+    let pending: Vec<&mut KillTarget> = targets.iter_mut().filter(|t| t.error.is_none()).collect();
+    let pids: Vec<u32> = pending.iter().map(|t| t.pid).collect();
+    // The guard has already run above.
+    for (target, (report, error)) in pending
+        .into_iter()
+        .zip(process_control::kill_all(&pids, options, table, None))
+    {
+        target.report = Some(report);
+        target.error = error;
+    }
+    Ok(targets)
+}
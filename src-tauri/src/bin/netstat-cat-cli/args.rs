@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use netstat_cat::query::Filter;
+
+pub const USAGE: &str = "\
+Usage: netstat-cat-cli [OPTIONS]
+
+List network and Unix sockets with their owning processes.
+
+Options:
+  -f, --format <FORMAT>    table (default), json, ndjson or csv
+  -w, --watch              Keep sampling until interrupted
+  -i, --interval <SECS>    Seconds between samples in watch mode [default: 2]
+  -p, --protocol <PROTO>   Only rows whose protocol starts with PROTO (tcp, udp6, unix, ...)
+  -s, --state <STATE>      Only rows in STATE (LISTEN, ESTABLISHED, ...)
+  -l, --listening          Only listening TCP and Unix sockets, and UDP sockets
+      --port <PORT>        Only rows with PORT as local or remote port
+      --pid <PID>          Only rows owned (possibly shared) by PID
+      --filter <EXPR>      Only rows matching EXPR, same syntax as the app's search box
+                           (e.g. 'lport>=8000 && process=node*')
+      --source <NAME>      Socket backend to use (one of the sources listed below)
+  -h, --help               Print this help
+  -V, --version            Print the version
+
+In watch mode, json prints one compact array per sample, ndjson prints
+added/changed/removed events, csv adds a leading sampled_at column.
+
+Exit status:
+  0  at least one row matched
+  1  no rows matched
+  2  invalid command line
+  3  sockets could not be enumerated
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub format: Format,
+    pub watch: bool,
+    pub interval: Duration,
+    pub protocol: Option<String>,
+    pub state: Option<String>,
+    pub listening: bool,
+    pub port: Option<u16>,
+    pub pid: Option<u32>,
+    pub filter: Option<Filter>,
+    pub source: Option<String>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            format: Format::Table,
+            watch: false,
+            interval: Duration::from_secs(2),
+            protocol: None,
+            state: None,
+            listening: false,
+            port: None,
+            pid: None,
+            filter: None,
+            source: None,
+        }
+    }
+}
+
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+pub fn parse(raw: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = Args::default();
+    let mut raw = raw.into_iter();
+
+    while let Some(arg) = raw.next() {
+        // Accept both `--flag value` and `--flag=value`.
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| raw.next())
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "-w" | "--watch" => args.watch = true,
+            "-l" | "--listening" => args.listening = true,
+            "-f" | "--format" => {
+                args.format = match value(&flag)?.as_str() {
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format '{other}'")),
+                }
+            }
+            "-i" | "--interval" => {
+                let secs: f64 = value(&flag)?
+                    .parse()
+                    .map_err(|_| "--interval expects a number of seconds".to_string())?;
+                if !(secs.is_finite() && secs > 0.0) {
+                    return Err("--interval must be greater than zero".to_string());
+                }
+                args.interval = Duration::from_secs_f64(secs);
+            }
+            "-p" | "--protocol" => args.protocol = Some(value(&flag)?.to_lowercase()),
+            "-s" | "--state" => args.state = Some(value(&flag)?.to_uppercase()),
+            "--port" => {
+                args.port = Some(
+                    value(&flag)?
+                        .parse()
+                        .map_err(|_| "--port expects a number between 0 and 65535".to_string())?,
+                )
+            }
+            "--pid" => {
+                args.pid = Some(
+                    value(&flag)?
+                        .parse()
+                        .map_err(|_| "--pid expects a number".to_string())?,
+                )
+            }
+            "--filter" => {
+                let expr = value(&flag)?;
+                args.filter = Some(Filter::parse(&expr).map_err(|e| {
+                    // Underline the offending part of the expression.
+                    let pad = " ".repeat(e.span.start);
+                    let marks = "^".repeat((e.span.end - e.span.start).max(1));
+                    format!("invalid --filter: {}\n  {expr}\n  {pad}{marks}", e.message)
+                })?);
+            }
+            "--source" => args.source = Some(value(&flag)?),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    Ok(Command::Run(args))
+}
@@ -0,0 +1,179 @@
+//! Headless front end sharing the collection core with the desktop app.
+
+mod args;
+mod output;
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::thread;
+
+use args::{Args, Command, Format};
+use netstat_cat::changes::diff_rows;
+use netstat_cat::error::NetstatCatError;
+use netstat_cat::export::ExportFormat;
+use netstat_cat::netstat::{self, SocketSource};
+use netstat_cat::process_info::ProcessInfo;
+use netstat_cat::process_table::ProcessTable;
+use netstat_cat::tracker::{now_ms, ConnectionTracker};
+
+const EXIT_NO_MATCH: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_COLLECTION: u8 = 3;
+
+fn main() -> ExitCode {
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(args)) => args,
+        Ok(Command::Help) => {
+            print!("{}", args::USAGE);
+            println!("\nSources: {}", netstat::source_names().join(", "));
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Version) => {
+            println!("netstat-cat-cli {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("netstat-cat-cli: {e}");
+            eprintln!("Try 'netstat-cat-cli --help' for more information.");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let source = match &args.source {
+        Some(name) => match netstat::source_by_name(name) {
+            Some(source) => source,
+            None => {
+                eprintln!(
+                    "netstat-cat-cli: unknown source '{name}' (available: {})",
+                    netstat::source_names().join(", ")
+                );
+                return ExitCode::from(EXIT_USAGE);
+            }
+        },
+        None => netstat::default_source(),
+    };
+
+    let result = if args.watch {
+        watch(&args, source.as_ref())
+    } else {
+        once(&args, source.as_ref())
+    };
+
+    match result {
+        Ok(code) => code,
+        // `netstat-cat-cli | head` closing the pipe is not an error.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("netstat-cat-cli: {e}");
+            ExitCode::from(EXIT_COLLECTION)
+        }
+    }
+}
+
+fn sample(
+    args: &Args,
+    source: &dyn SocketSource,
+    table: &ProcessTable,
+) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    let mut rows = netstat::fetch_process_info_list_from(source, table)?;
+    rows.retain(|row| matches(args, row));
+    Ok(rows)
+}
+
+fn matches(args: &Args, row: &ProcessInfo) -> bool {
+    if let Some(protocol) = &args.protocol {
+        if !row.protocol.starts_with(protocol.as_str()) {
+            return false;
+        }
+    }
+    if let Some(state) = &args.state {
+        if !row.state.eq_ignore_ascii_case(state) {
+            return false;
+        }
+    }
+    if args.listening && row.state != "LISTEN" && !row.protocol.starts_with("udp") {
+        return false;
+    }
+    if let Some(port) = args.port {
+        if row.local.port != Some(port) && row.remote.port != Some(port) {
+            return false;
+        }
+    }
+    if let Some(pid) = args.pid {
+        if !row.pids.contains(&pid) {
+            return false;
+        }
+    }
+    if let Some(filter) = &args.filter {
+        if !filter.matches(row) {
+            return false;
+        }
+    }
+    true
+}
+
+fn once(args: &Args, source: &dyn SocketSource) -> io::Result<ExitCode> {
+    let rows = match sample(args, source, &ProcessTable::default()) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("netstat-cat-cli: {e}");
+            return Ok(ExitCode::from(EXIT_COLLECTION));
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    match args.format {
+        Format::Table => output::table(&mut out, &rows)?,
+        Format::Json => output::export(&mut out, &rows, ExportFormat::Json)?,
+        Format::Ndjson => output::export(&mut out, &rows, ExportFormat::Ndjson)?,
+        Format::Csv => output::export(&mut out, &rows, ExportFormat::Csv)?,
+    }
+    out.flush()?;
+
+    Ok(if rows.is_empty() {
+        ExitCode::from(EXIT_NO_MATCH)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Samples until interrupted. Collection errors are reported and retried on
+/// the next tick rather than ending the session.
+fn watch(args: &Args, source: &dyn SocketSource) -> io::Result<ExitCode> {
+    let tracker = ConnectionTracker::default();
+    let table = ProcessTable::default();
+    let mut previous = HashMap::new();
+    let mut sequence = 0;
+
+    if args.format == Format::Csv {
+        output::csv_header(&mut io::stdout().lock())?;
+    }
+
+    loop {
+        match sample(args, source, &table) {
+            Ok(mut rows) => {
+                tracker.annotate(&mut rows);
+                let mut out = io::stdout().lock();
+                match args.format {
+                    Format::Table => {
+                        // Clear the screen and home the cursor before redrawing.
+                        write!(out, "\x1b[2J\x1b[H")?;
+                        output::table(&mut out, &rows)?;
+                    }
+                    Format::Json => output::json_line(&mut out, &rows)?,
+                    Format::Ndjson => {
+                        sequence += 1;
+                        let diff = diff_rows(&mut previous, rows, sequence, false);
+                        output::ndjson_events(&mut out, &diff)?;
+                    }
+                    Format::Csv => output::csv_rows(&mut out, &rows, now_ms())?,
+                }
+                out.flush()?;
+            }
+            Err(e) => eprintln!("netstat-cat-cli: {e}"),
+        }
+
+        thread::sleep(args.interval);
+    }
+}
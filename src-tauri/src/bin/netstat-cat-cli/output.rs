@@ -0,0 +1,164 @@
+use std::io::{self, Write};
+
+use netstat_cat::changes::ConnectionDiff;
+use netstat_cat::export::{self, Column, ExportFormat, Record};
+use netstat_cat::process_info::{
+    display_address, has_remote, AddressPort, ProcessInfo, ProcessState,
+};
+use serde::Serialize;
+
+/// Columns of the CSV, JSON and NDJSON output.
+const COLUMNS: [Column; 12] = [
+    Column::Id,
+    Column::Protocol,
+    Column::LocalAddress,
+    Column::LocalPort,
+    Column::RemoteAddress,
+    Column::RemotePort,
+    Column::State,
+    Column::Pid,
+    Column::ProcessName,
+    Column::Pids,
+    Column::Uid,
+    Column::Inode,
+];
+
+pub fn table(out: &mut impl Write, rows: &[ProcessInfo]) -> io::Result<()> {
+    let header = [
+        "Proto",
+        "Local Address",
+        "Foreign Address",
+        "State",
+        "PID/Program name",
+    ];
+    let lines: Vec<[String; 5]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.protocol.clone(),
+                local(row),
+                remote(row),
+                row.state.clone(),
+                owners(row),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    write_table_line(out, &header.map(String::from), &widths)?;
+    for line in &lines {
+        write_table_line(out, line, &widths)?;
+    }
+    Ok(())
+}
+
+fn write_table_line(
+    out: &mut impl Write,
+    cells: &[String; 5],
+    widths: &[usize; 5],
+) -> io::Result<()> {
+    let mut line = String::new();
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if i + 1 == cells.len() {
+            line.push_str(cell);
+        } else {
+            line.push_str(&format!("{cell:<width$}  "));
+        }
+    }
+    writeln!(out, "{}", line.trim_end())
+}
+
+/// Endpoints as the app's table shows them: wildcard addresses as `0.0.0.0`
+/// or `[::]`, no remote end as `-`, and brackets around IPv6 addresses.
+fn local(row: &ProcessInfo) -> String {
+    if row.protocol.starts_with("unix") {
+        return row.local.address.clone().unwrap_or("(unnamed)".to_string());
+    }
+    endpoint(row, &row.local)
+}
+
+fn remote(row: &ProcessInfo) -> String {
+    if !has_remote(row) {
+        return "-".to_string();
+    }
+    endpoint(row, &row.remote)
+}
+
+fn endpoint(row: &ProcessInfo, ap: &AddressPort) -> String {
+    let address = display_address(&row.protocol, ap.address.as_deref()).unwrap_or_default();
+    match ap.port {
+        Some(port) if address.contains(':') && !address.starts_with('[') => {
+            format!("[{address}]:{port}")
+        }
+        Some(port) => format!("{address}:{port}"),
+        None => address,
+    }
+}
+
+fn owners(row: &ProcessInfo) -> String {
+    if row.pids.is_empty() {
+        return "-".to_string();
+    }
+    let owners = row
+        .pids
+        .iter()
+        .zip(&row.process_names)
+        .map(|(pid, name)| format!("{pid}/{name}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    match row.process_state {
+        Some(ProcessState::Stopped | ProcessState::Tracing) => format!("{owners} (stopped)"),
+        _ => owners,
+    }
+}
+
+/// One-shot CSV, JSON or NDJSON, written like the app's exports.
+pub fn export(out: &mut impl Write, rows: &[ProcessInfo], format: ExportFormat) -> io::Result<()> {
+    export::export(out, rows, format, &COLUMNS)
+}
+
+/// One compact JSON array per sample, for watch mode.
+pub fn json_line(out: &mut impl Write, rows: &[ProcessInfo]) -> io::Result<()> {
+    export::json(out, rows, &COLUMNS, false)
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+enum Event<'a> {
+    Added { row: Record<'a> },
+    Changed { row: Record<'a> },
+    Removed { id: &'a str },
+}
+
+pub fn ndjson_events(out: &mut impl Write, diff: &ConnectionDiff) -> io::Result<()> {
+    let events = diff
+        .added
+        .iter()
+        .map(|row| Event::Added {
+            row: Record::new(row, &COLUMNS),
+        })
+        .chain(diff.changed.iter().map(|row| Event::Changed {
+            row: Record::new(row, &COLUMNS),
+        }))
+        .chain(diff.removed.iter().map(|id| Event::Removed { id }));
+    for event in events {
+        serde_json::to_writer(&mut *out, &event)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// The header of watch mode's CSV, which adds a leading `sampled_at`.
+pub fn csv_header(out: &mut impl Write) -> io::Result<()> {
+    export::csv_header(out, &COLUMNS, &["sampled_at"])
+}
+
+pub fn csv_rows(out: &mut impl Write, rows: &[ProcessInfo], sampled_at: u64) -> io::Result<()> {
+    export::csv_rows(out, rows, &COLUMNS, &[sampled_at.to_string()])
+}
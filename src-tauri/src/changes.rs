@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::process_info::{ProcessInfo, ProcessState};
+
+/// Rows added, changed and removed between two consecutive samples.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiff {
+    /// Increases by one per emitted diff; a gap means the UI missed an event
+    /// and should resubscribe to get a fresh baseline.
+    pub sequence: u64,
+    pub added: Vec<ProcessInfo>,
+    pub changed: Vec<ProcessInfo>,
+    /// IDs of rows that disappeared.
+    pub removed: Vec<String>,
+}
+
+impl ConnectionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// How often the live view resends rows whose TCP metrics or process state
+/// moved without any other change.
+pub const METRICS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Compares a new sample against the rows the consumer already has, keeping
+/// `previous` in step with what was sent.
+///
+/// `age`, the `tcp_info` metrics and a process switching between running
+/// and sleeping move on almost every sample, so alone they only count as a
+/// change when `with_metrics` is set. Stopping or resuming always counts.
+pub fn diff_rows(
+    previous: &mut HashMap<String, ProcessInfo>,
+    rows: Vec<ProcessInfo>,
+    sequence: u64,
+    with_metrics: bool,
+) -> ConnectionDiff {
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    let mut next = HashMap::with_capacity(rows.len());
+
+    for row in rows {
+        let kept = match previous.remove(&row.id) {
+            None => {
+                added.push(row.clone());
+                row
+            }
+            Some(old) if differs(&old, &row, with_metrics) => {
+                changed.push(row.clone());
+                row
+            }
+            // Keep the row as sent, so the next metrics refresh compares
+            // against what the consumer shows.
+            Some(old) => old,
+        };
+        next.insert(kept.id.clone(), kept);
+    }
+
+    let removed = previous.drain().map(|(key, _)| key).collect();
+    *previous = next;
+
+    ConnectionDiff {
+        sequence,
+        added,
+        changed,
+        removed,
+    }
+}
+
+fn differs(old: &ProcessInfo, new: &ProcessInfo, with_metrics: bool) -> bool {
+    let settled = |row: &ProcessInfo| ProcessInfo {
+        age: None,
+        tcp_info: None,
+        process_state: None,
+        ..row.clone()
+    };
+    let stopped = |row: &ProcessInfo| {
+        matches!(
+            row.process_state,
+            Some(ProcessState::Stopped | ProcessState::Tracing)
+        )
+    };
+
+    settled(old) != settled(new)
+        || stopped(old) != stopped(new)
+        || with_metrics && (old.tcp_info != new.tcp_info || old.process_state != new.process_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_info::TcpInfo;
+
+    fn row(state: &str, bytes_acked: u64) -> ProcessInfo {
+        ProcessInfo {
+            id: "a".into(),
+            protocol: "tcp".into(),
+            state: state.into(),
+            tcp_info: Some(TcpInfo {
+                bytes_acked: Some(bytes_acked),
+                ..TcpInfo::default()
+            }),
+            age: Some(bytes_acked),
+            ..ProcessInfo::default()
+        }
+    }
+
+    #[test]
+    fn counters_alone_are_not_a_change() {
+        let mut previous = HashMap::new();
+        let diff = diff_rows(&mut previous, vec![row("ESTABLISHED", 1)], 1, false);
+        assert_eq!(diff.added, vec![row("ESTABLISHED", 1)]);
+
+        let diff = diff_rows(&mut previous, vec![row("ESTABLISHED", 2)], 2, false);
+        assert!(diff.is_empty());
+
+        // A real change carries the current counters.
+        let diff = diff_rows(&mut previous, vec![row("CLOSE_WAIT", 3)], 3, false);
+        assert_eq!(diff.changed, vec![row("CLOSE_WAIT", 3)]);
+
+        let diff = diff_rows(&mut previous, Vec::new(), 4, false);
+        assert_eq!(diff.removed, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn metrics_refresh_compares_against_the_sent_row() {
+        let mut previous = HashMap::new();
+        diff_rows(&mut previous, vec![row("ESTABLISHED", 1)], 1, false);
+        diff_rows(&mut previous, vec![row("ESTABLISHED", 2)], 2, false);
+
+        // Unchanged since the last sample, but not since the last send.
+        let diff = diff_rows(&mut previous, vec![row("ESTABLISHED", 2)], 3, true);
+        assert_eq!(diff.changed, vec![row("ESTABLISHED", 2)]);
+
+        let diff = diff_rows(&mut previous, vec![row("ESTABLISHED", 2)], 4, true);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn only_stopping_is_a_process_state_change() {
+        let with_state = |state| ProcessInfo {
+            process_state: Some(state),
+            ..row("ESTABLISHED", 1)
+        };
+        let mut previous = HashMap::new();
+        diff_rows(
+            &mut previous,
+            vec![with_state(ProcessState::Running)],
+            1,
+            false,
+        );
+
+        let diff = diff_rows(
+            &mut previous,
+            vec![with_state(ProcessState::Sleeping)],
+            2,
+            false,
+        );
+        assert!(diff.is_empty());
+
+        let diff = diff_rows(
+            &mut previous,
+            vec![with_state(ProcessState::Stopped)],
+            3,
+            false,
+        );
+        assert_eq!(diff.changed, vec![with_state(ProcessState::Stopped)]);
+    }
+}
@@ -0,0 +1,484 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::audit::{self, AuditEntry, AuditLog, Owners};
+use crate::batch_kill::{self, KillTarget};
+use crate::dns::ReverseDns;
+use crate::error::NetstatCatError;
+use crate::export::{self, Column, ExportFormat};
+use crate::free_port::{self, FreePortOptions, FreePortReport, PortProtocol};
+use crate::history::{HistoryEntry, Retention, TimeRange};
+use crate::import::{self, ImportFormat};
+use crate::netstat;
+use crate::process_control::{self, KillOptions, KillReport, Signal, TreeKillEntry, TreeOrder};
+use crate::process_details::{self, ProcessDetails};
+use crate::process_info::ProcessInfo;
+use crate::process_table::{ProcessTable, RefreshStats};
+use crate::protection::{Protection, ProtectionPolicy};
+use crate::query::{self, Filter};
+use crate::recorder::{self, Recorder};
+use crate::sampler::Sampler;
+use crate::scanner::Scanner;
+use crate::snapshot::Snapshot;
+use crate::snapshot_diff::{self, SnapshotDiff};
+use crate::tracker::{self, ConnectionTracker};
+use crate::watcher;
+
+/// Runs blocking work on Tauri's blocking pool so the async runtime (and
+/// with it the UI's IPC) never waits on syscalls.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, NetstatCatError> + Send + 'static,
+) -> Result<T, NetstatCatError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| NetstatCatError::Io {
+            message: format!("Background task failed: {e}"),
+            errno: None,
+        })?
+}
+
+/// Samples the connections, joining the scan already running if there is
+/// one, and annotates the rows with their age and remote host names.
+pub(crate) fn scan(app: &AppHandle) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    let table = app.state::<ProcessTable>();
+    let mut rows = app.state::<Scanner>().scan(|token| {
+        netstat::fetch_process_info_list_with(netstat::default_source().as_ref(), &table, &|| {
+            token.is_cancelled()
+        })
+    })?;
+    app.state::<ConnectionTracker>().annotate(&mut rows);
+    app.state::<ReverseDns>().annotate(&mut rows);
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_process_info_list(app: AppHandle) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    blocking(move || scan(&app)).await
+}
+
+/// Same as `get_process_info_list`, keeping only rows that match `filter`
+/// (the search box syntax, see `filters_en.md`).
+#[tauri::command]
+pub async fn get_filtered_process_info_list(
+    app: AppHandle,
+    filter: String,
+) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    let filter = Filter::parse(&filter)?;
+    // Annotated before filtering so rows hidden by the filter keep their age.
+    blocking(move || Ok(query::filter_rows(scan(&app)?, &filter))).await
+}
+
+/// Abandons the scan in progress; its callers get a `cancelled` error.
+#[tauri::command]
+pub fn cancel_scan(scanner: State<Scanner>) {
+    scanner.cancel();
+}
+
+/// Turns reverse DNS of remote addresses on or off (it is on by default).
+#[tauri::command]
+pub fn set_reverse_dns(dns: State<ReverseDns>, enabled: bool) {
+    dns.set_enabled(enabled);
+}
+
+#[tauri::command]
+pub fn is_reverse_dns_enabled(dns: State<ReverseDns>) -> bool {
+    dns.is_enabled()
+}
+
+/// Closes one TCP connection (by row `id`) while its process keeps running.
+#[tauri::command]
+pub async fn close_connection(app: AppHandle, id: String) -> Result<(), NetstatCatError> {
+    blocking(move || {
+        let rows = scan(&app)?;
+        let row = rows
+            .iter()
+            .find(|row| row.id == id)
+            .ok_or(NetstatCatError::ConnectionNotFound { id })?;
+        netstat::close_connection(row)
+    })
+    .await
+}
+
+/// Validates a filter while the user types.
+#[tauri::command]
+pub fn parse_filter(filter: String) -> Result<(), NetstatCatError> {
+    Filter::parse(&filter)?;
+    Ok(())
+}
+
+/// Starts (or restarts) pushing changes from the background sampler as
+/// `connections-diff` events instead of being polled.
+#[tauri::command]
+pub fn subscribe_connections(sampler: State<Sampler>, interval_ms: Option<u64>) {
+    let interval = Duration::from_millis(interval_ms.unwrap_or(watcher::DEFAULT_INTERVAL_MS));
+    watcher::subscribe(&sampler, interval);
+}
+
+// Waits for the sampler to finish handing the live view a sample.
+#[tauri::command(async)]
+pub fn unsubscribe_connections(sampler: State<Sampler>) {
+    watcher::unsubscribe(&sampler);
+}
+
+/// Starts recording connection history to the app's data directory.
+// Runs off the main thread: it may open the database and stop a recorder.
+#[tauri::command(async)]
+pub fn start_history_recording(
+    recorder: State<Recorder>,
+    sampler: State<Sampler>,
+    interval_ms: Option<u64>,
+    retention: Option<Retention>,
+) -> Result<(), NetstatCatError> {
+    let interval = Duration::from_millis(interval_ms.unwrap_or(recorder::DEFAULT_INTERVAL_MS));
+    recorder.start(&sampler, interval, retention.unwrap_or_default())
+}
+
+// Waits for the sampler to finish recording a sample.
+#[tauri::command(async)]
+pub fn stop_history_recording(recorder: State<Recorder>, sampler: State<Sampler>) {
+    recorder.stop(&sampler);
+}
+
+#[tauri::command]
+pub fn is_history_recording(recorder: State<Recorder>, sampler: State<Sampler>) -> bool {
+    recorder.is_recording(&sampler)
+}
+
+/// Recorded connections open at any point in `time_range` that match
+/// `filter` (the search box syntax), e.g. `raddr=10.0.0.5` over the
+/// last hour.
+#[tauri::command]
+pub async fn query_history(
+    app: AppHandle,
+    filter: Option<String>,
+    time_range: Option<TimeRange>,
+) -> Result<Vec<HistoryEntry>, NetstatCatError> {
+    let filter = Filter::parse(filter.as_deref().unwrap_or(""))?;
+    blocking(move || {
+        let history = app.state::<Recorder>().history()?;
+        history.query(&filter, time_range.unwrap_or_default())
+    })
+    .await
+}
+
+/// Where files are saved unless the user picks a path.
+fn save_dir(app: &AppHandle) -> Result<PathBuf, NetstatCatError> {
+    app.path()
+        .download_dir()
+        .or_else(|_| app.path().home_dir())
+        .map_err(|e| NetstatCatError::Io {
+            message: format!("No folder to save to: {e}"),
+            errno: None,
+        })
+}
+
+/// Samples the connections and writes them with this host's metadata to
+/// `path` (by default a new file in the Downloads folder). Returns the path
+/// written.
+#[tauri::command]
+pub async fn save_snapshot(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<String, NetstatCatError> {
+    blocking(move || {
+        let snapshot = Snapshot::capture(scan(&app)?);
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => save_dir(&app)?.join(snapshot.file_name()),
+        };
+        snapshot.save(&path)?;
+        Ok(path.display().to_string())
+    })
+    .await
+}
+
+/// Writes the connections matching `filter` to `path` (by default a new
+/// file in the Downloads folder) and returns the path written. `columns`
+/// defaults to those of the table. A snapshot being viewed passes its rows
+/// in `connections`; otherwise the live connections are sampled.
+#[tauri::command]
+pub async fn export_connections(
+    app: AppHandle,
+    format: ExportFormat,
+    filter: Option<String>,
+    path: Option<String>,
+    columns: Option<Vec<Column>>,
+    connections: Option<Vec<ProcessInfo>>,
+) -> Result<String, NetstatCatError> {
+    let filter = filter.as_deref().map(Filter::parse).transpose()?;
+    blocking(move || {
+        let mut rows = match connections {
+            Some(rows) => rows,
+            None => scan(&app)?,
+        };
+        if let Some(filter) = &filter {
+            rows = query::filter_rows(rows, filter);
+        }
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => save_dir(&app)?.join(format!(
+                "netstat-cat-{}.{}",
+                tracker::now_ms(),
+                format.extension()
+            )),
+        };
+        let io_err = |e: std::io::Error| NetstatCatError::Io {
+            message: format!("{}: {e}", path.display()),
+            errno: e.raw_os_error(),
+        };
+        let mut out = BufWriter::new(File::create(&path).map_err(io_err)?);
+        export::export(&mut out, &rows, format, &columns.unwrap_or_default())
+            .and_then(|()| out.flush())
+            .map_err(io_err)?;
+        Ok(path.display().to_string())
+    })
+    .await
+}
+
+/// Reads a snapshot for the read-only view; nothing live is touched.
+#[tauri::command]
+pub async fn load_snapshot(path: String) -> Result<Snapshot, NetstatCatError> {
+    blocking(move || Snapshot::load(Path::new(&path))).await
+}
+
+/// Reads `ss`, `netstat` or `lsof` output from another machine into a
+/// snapshot for the read-only view. The format is detected unless given.
+#[tauri::command]
+pub async fn import_connections(
+    path: String,
+    format: Option<ImportFormat>,
+) -> Result<Snapshot, NetstatCatError> {
+    blocking(move || import::import(Path::new(&path), format)).await
+}
+
+/// Compares the snapshot at `before` with the one at `after`, or with the
+/// live connections when `after` is omitted.
+#[tauri::command]
+pub async fn diff_snapshots(
+    app: AppHandle,
+    before: String,
+    after: Option<String>,
+) -> Result<SnapshotDiff, NetstatCatError> {
+    blocking(move || {
+        let before = Snapshot::load(Path::new(&before))?;
+        let after = match after {
+            Some(after) => Snapshot::load(Path::new(&after))?,
+            None => Snapshot::capture(scan(&app)?),
+        };
+        Ok(snapshot_diff::diff_snapshots(&before, &after))
+    })
+    .await
+}
+
+// Runs off the main thread: it is called on hover and refreshes sysinfo.
+#[tauri::command(async)]
+pub fn get_process_path(pid: u32) -> Result<String, NetstatCatError> {
+    process_details::process_path(pid)
+}
+
+// Runs off the main thread: sampling CPU usage takes a moment.
+#[tauri::command(async)]
+pub fn get_process_details(pid: u32) -> Result<ProcessDetails, NetstatCatError> {
+    process_details::process_details(pid)
+}
+
+/// Kill settings shared by the kill commands; every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KillArgs {
+    /// `TERM` (default), `INT`, `HUP`, `KILL`, `STOP`, `CONT` or a number.
+    signal: Option<String>,
+    /// Follow up with `SIGKILL` after `grace_ms`; on by default.
+    escalate: Option<bool>,
+    grace_ms: Option<u64>,
+    /// Skip the protection policy.
+    force: bool,
+}
+
+impl KillArgs {
+    fn options(&self) -> Result<KillOptions, NetstatCatError> {
+        let signal = match &self.signal {
+            Some(signal) => signal.parse()?,
+            None => Signal::Term,
+        };
+        let grace =
+            Duration::from_millis(self.grace_ms.unwrap_or(process_control::DEFAULT_GRACE_MS));
+        Ok(KillOptions {
+            signal,
+            escalate_after: self.escalate.unwrap_or(true).then_some(grace),
+        })
+    }
+}
+
+/// Appends to the audit log. The kills have happened either way, so a log
+/// that cannot be written must not turn the command into a failure; the UI
+/// hears about it through [`audit::ERROR_EVENT`] instead.
+fn record(app: &AppHandle, log: &AuditLog, entries: &[AuditEntry]) {
+    if let Err(e) = log.record(entries) {
+        let _ = app.emit(audit::ERROR_EVENT, &e);
+    }
+}
+
+#[tauri::command(async)]
+pub fn kill_process(
+    app: AppHandle,
+    protection: State<Protection>,
+    audit: State<AuditLog>,
+    table: State<ProcessTable>,
+    pid: u32,
+    options: Option<KillArgs>,
+) -> Result<KillReport, NetstatCatError> {
+    let args = options.unwrap_or_default();
+    let options = args.options()?;
+    let guard = protection.guard(args.force, &table);
+    let owners = Owners::resolve(&table, &[pid]);
+    let result = process_control::kill(pid, options, &table, guard.as_ref());
+    let entry = owners.entry(
+        pid,
+        options.signal,
+        result.as_ref().ok(),
+        result.as_ref().err(),
+    );
+    record(&app, &audit, &[entry]);
+    result
+}
+
+/// Stops `pid` without killing it, e.g. to watch a client's peer time out.
+#[tauri::command(async)]
+pub fn suspend_process(
+    protection: State<Protection>,
+    table: State<ProcessTable>,
+    pid: u32,
+    force: Option<bool>,
+) -> Result<(), NetstatCatError> {
+    let guard = protection.guard(force.unwrap_or(false), &table);
+    process_control::suspend(pid, &table, guard.as_ref())
+}
+
+#[tauri::command]
+pub fn resume_process(table: State<ProcessTable>, pid: u32) -> Result<(), NetstatCatError> {
+    process_control::resume(pid, &table)
+}
+
+/// Signals several processes at once, each PID once however often it is
+/// listed. With `dry_run` nothing is sent: the result lists the name, owner
+/// and ports of every process and which ones the protection policy would
+/// refuse.
+#[tauri::command(async)]
+pub fn kill_processes(
+    app: AppHandle,
+    protection: State<Protection>,
+    audit: State<AuditLog>,
+    table: State<ProcessTable>,
+    pids: Vec<u32>,
+    dry_run: Option<bool>,
+    options: Option<KillArgs>,
+) -> Result<Vec<KillTarget>, NetstatCatError> {
+    let args = options.unwrap_or_default();
+    let options = args.options()?;
+    let dry_run = dry_run.unwrap_or(false);
+    let guard = protection.guard(args.force, &table);
+    let targets = batch_kill::kill_processes(&pids, options, dry_run, &table, guard.as_ref())?;
+    if !dry_run {
+        let entries: Vec<AuditEntry> = targets
+            .iter()
+            .map(|target| AuditEntry::from_target(target, options.signal))
+            .collect();
+        record(&app, &audit, &entries);
+    }
+    Ok(targets)
+}
+
+/// `kill_process` for `pid` and all of its descendants, children first
+/// unless `order` is `"topDown"`.
+#[tauri::command(async)]
+pub fn kill_process_tree(
+    app: AppHandle,
+    protection: State<Protection>,
+    audit: State<AuditLog>,
+    table: State<ProcessTable>,
+    pid: u32,
+    order: Option<TreeOrder>,
+    options: Option<KillArgs>,
+) -> Result<Vec<TreeKillEntry>, NetstatCatError> {
+    let args = options.unwrap_or_default();
+    let options = args.options()?;
+    let guard = protection.guard(args.force, &table);
+    let owners = Owners::capture(&table);
+    let entries = process_control::kill_tree(
+        pid,
+        order.unwrap_or(TreeOrder::BottomUp),
+        options,
+        &table,
+        guard.as_ref(),
+    )?;
+    let log: Vec<AuditEntry> = entries
+        .iter()
+        .map(|e| owners.entry(e.pid, options.signal, Some(&e.report), e.error.as_ref()))
+        .collect();
+    record(&app, &audit, &log);
+    Ok(entries)
+}
+
+/// Gets rid of whatever listens on `port` (TCP and UDP unless `protocol`
+/// narrows it down) and waits up to `timeout_ms` for the port to be free.
+/// With `kill: false` it only reports the holders.
+// The managed states count towards the limit but are not part of the IPC
+// arguments.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(async)]
+pub fn free_port(
+    app: AppHandle,
+    protection: State<Protection>,
+    audit: State<AuditLog>,
+    table: State<ProcessTable>,
+    port: u16,
+    protocol: Option<String>,
+    kill: Option<bool>,
+    options: Option<KillArgs>,
+    timeout_ms: Option<u64>,
+) -> Result<FreePortReport, NetstatCatError> {
+    let args = options.unwrap_or_default();
+    let options = FreePortOptions {
+        protocol: protocol.as_deref().map(PortProtocol::parse).transpose()?,
+        kill: match kill.unwrap_or(true) {
+            true => Some(args.options()?),
+            false => None,
+        },
+        timeout: Duration::from_millis(timeout_ms.unwrap_or(free_port::DEFAULT_TIMEOUT_MS)),
+    };
+    let guard = protection.guard(args.force, &table);
+    let owners = Owners::capture(&table);
+    let report = free_port::free_port(port, &options, &table, guard.as_ref())?;
+    if let Some(kill) = options.kill {
+        let log: Vec<AuditEntry> = report
+            .holders
+            .iter()
+            .filter(|h| h.report.is_some())
+            .map(|h| owners.entry(h.pid, kill.signal, h.report.as_ref(), h.error.as_ref()))
+            .collect();
+        record(&app, &audit, &log);
+    }
+    Ok(report)
+}
+
+/// How long the last process table refresh took.
+#[tauri::command]
+pub fn get_process_table_stats(table: State<ProcessTable>) -> RefreshStats {
+    table.stats()
+}
+
+#[tauri::command]
+pub fn get_protection_policy(protection: State<Protection>) -> ProtectionPolicy {
+    protection.policy()
+}
+
+#[tauri::command]
+pub fn set_protection_policy(protection: State<Protection>, policy: ProtectionPolicy) {
+    protection.set_policy(policy);
+}
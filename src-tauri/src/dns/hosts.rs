@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+#[cfg(windows)]
+const HOSTS_PATH: &str = r"C:\Windows\System32\drivers\etc\hosts";
+#[cfg(not(windows))]
+const HOSTS_PATH: &str = "/etc/hosts";
+
+/// Names from a hosts file, by address. The first name listed for an
+/// address wins, as with the system resolver.
+#[derive(Debug, Clone, Default)]
+pub struct HostsFile {
+    names: HashMap<IpAddr, String>,
+}
+
+impl HostsFile {
+    /// The system hosts file; empty if it cannot be read.
+    pub fn load() -> Self {
+        Self::read(Path::new(HOSTS_PATH)).unwrap_or_default()
+    }
+
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut names = HashMap::new();
+        for line in text.lines() {
+            let line = line.split_once('#').map_or(line, |(data, _comment)| data);
+            let mut fields = line.split_whitespace();
+            let (Some(address), Some(name)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // Scoped addresses (fe80::1%lo0) cannot be matched anyway.
+            if let Ok(ip) = address.parse::<IpAddr>() {
+                names.entry(ip).or_insert_with(|| name.to_string());
+            }
+        }
+        HostsFile { names }
+    }
+
+    pub fn get(&self, ip: &IpAddr) -> Option<&str> {
+        self.names.get(ip).map(String::as_str)
+    }
+}
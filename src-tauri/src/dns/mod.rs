@@ -0,0 +1,236 @@
+//! Reverse DNS for remote addresses. A scan never waits for it: a name not
+//! cached yet is looked up in the background, and rows carry it from the
+//! next scan on.
+
+mod hosts;
+mod system;
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::process_info::ProcessInfo;
+
+pub use hosts::HostsFile;
+pub use system::SystemResolver;
+
+/// Where names come from; tests plug in their own.
+pub trait Resolver: Send + Sync + 'static {
+    /// The name of `ip`. Blocks; `Ok(None)` if it has none.
+    fn reverse(&self, ip: IpAddr) -> io::Result<Option<String>>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DnsConfig {
+    /// How long a name is trusted.
+    pub ttl: Duration,
+    /// How long a failed lookup (no name, error or timeout) is remembered.
+    pub negative_ttl: Duration,
+    /// Most addresses cached; the least recently used go first.
+    pub capacity: usize,
+    /// Most lookups running at once.
+    pub max_concurrent: usize,
+    /// How long to wait for one lookup.
+    pub timeout: Duration,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        DnsConfig {
+            ttl: Duration::from_secs(10 * 60),
+            negative_ttl: Duration::from_secs(60),
+            capacity: 4096,
+            max_concurrent: 4,
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+struct Entry {
+    hostname: Option<String>,
+    expires: Instant,
+    /// `State::clock` at the last hit, for LRU eviction.
+    used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<IpAddr, Entry>,
+    queue: VecDeque<IpAddr>,
+    queued: HashSet<IpAddr>,
+    /// Lookups running, and whether their worker stopped waiting for them.
+    in_flight: HashMap<IpAddr, bool>,
+    workers: usize,
+    /// Timed-out lookups still running. Each keeps a thread busy, so no new
+    /// lookups start while there are `max_concurrent` of them.
+    abandoned: usize,
+    clock: u64,
+}
+
+struct Inner {
+    resolver: Box<dyn Resolver>,
+    hosts: HostsFile,
+    config: DnsConfig,
+    state: Mutex<State>,
+}
+
+/// Cached reverse lookups, answered from the hosts file first.
+pub struct ReverseDns {
+    inner: Arc<Inner>,
+    enabled: AtomicBool,
+}
+
+impl Default for ReverseDns {
+    fn default() -> Self {
+        ReverseDns::new(SystemResolver, HostsFile::load(), DnsConfig::default())
+    }
+}
+
+impl ReverseDns {
+    pub fn new(resolver: impl Resolver, hosts: HostsFile, config: DnsConfig) -> Self {
+        ReverseDns {
+            inner: Arc::new(Inner {
+                resolver: Box::new(resolver),
+                hosts,
+                config,
+                state: Mutex::new(State::default()),
+            }),
+            enabled: AtomicBool::new(true),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// While disabled, `annotate` leaves rows alone and nothing is sent to
+    /// the resolver.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The cached name of `ip`. On a miss (or an expired entry, whose name
+    /// is returned meanwhile) a background lookup is queued.
+    pub fn lookup(&self, ip: IpAddr) -> Option<String> {
+        if let Some(name) = self.inner.hosts.get(&ip) {
+            return Some(name.to_string());
+        }
+        let now = Instant::now();
+        let mut state = self.inner.state.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+        let (hostname, fresh) = match state.entries.get_mut(&ip) {
+            Some(entry) => {
+                entry.used = clock;
+                (entry.hostname.clone(), entry.expires > now)
+            }
+            None => (None, false),
+        };
+        if !fresh && !state.in_flight.contains_key(&ip) && state.queued.insert(ip) {
+            state.queue.push_back(ip);
+        }
+        // Also picks up a queue left behind while lookups were stuck.
+        let limit = self.inner.config.max_concurrent;
+        if !state.queue.is_empty() && state.workers < limit && state.abandoned < limit {
+            state.workers += 1;
+            let inner = self.inner.clone();
+            thread::spawn(move || work(inner));
+        }
+        hostname
+    }
+
+    /// Fills `remote.hostname` on Internet rows with what is known so far.
+    pub fn annotate(&self, rows: &mut [ProcessInfo]) {
+        if !self.is_enabled() {
+            return;
+        }
+        for row in rows
+            .iter_mut()
+            .filter(|row| !row.protocol.starts_with("unix"))
+        {
+            let ip = row.remote.address.as_deref().and_then(|a| a.parse().ok());
+            row.remote.hostname = ip.and_then(|ip| self.lookup(ip));
+        }
+    }
+}
+
+impl Inner {
+    fn store(&self, state: &mut State, ip: IpAddr, hostname: Option<String>) {
+        let ttl = if hostname.is_some() {
+            self.config.ttl
+        } else {
+            self.config.negative_ttl
+        };
+        state.clock += 1;
+        let used = state.clock;
+        state.entries.insert(
+            ip,
+            Entry {
+                hostname,
+                expires: Instant::now() + ttl,
+                used,
+            },
+        );
+        if state.entries.len() > self.config.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(&ip, _)| ip);
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A worker: runs queued lookups one at a time until the queue is empty.
+fn work(inner: Arc<Inner>) {
+    loop {
+        let ip = {
+            let mut state = inner.state.lock().unwrap();
+            let next = if state.abandoned < inner.config.max_concurrent {
+                state.queue.pop_front()
+            } else {
+                None
+            };
+            let Some(ip) = next else {
+                state.workers -= 1;
+                return;
+            };
+            state.queued.remove(&ip);
+            state.in_flight.insert(ip, false);
+            ip
+        };
+
+        // The resolver cannot be interrupted, so it runs on its own thread.
+        // An answer arriving after the timeout is still cached.
+        let (done, finished) = mpsc::channel();
+        let lookup = inner.clone();
+        thread::spawn(move || {
+            let hostname = lookup.resolver.reverse(ip).ok().flatten();
+            let mut state = lookup.state.lock().unwrap();
+            if state.in_flight.remove(&ip) == Some(true) {
+                state.abandoned -= 1;
+            }
+            lookup.store(&mut state, ip, hostname);
+            let _ = done.send(());
+        });
+
+        if finished.recv_timeout(inner.config.timeout).is_err() {
+            let mut state = inner.state.lock().unwrap();
+            if let Some(abandoned) = state.in_flight.get_mut(&ip) {
+                *abandoned = true;
+                state.abandoned += 1;
+                inner.store(&mut state, ip, None);
+            }
+        }
+    }
+}
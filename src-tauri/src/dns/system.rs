@@ -0,0 +1,16 @@
+use std::io;
+use std::net::IpAddr;
+
+use super::Resolver;
+
+/// The operating system's resolver (`getnameinfo`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn reverse(&self, ip: IpAddr) -> io::Result<Option<String>> {
+        // `lookup_addr` asks for a name (NI_NAMEREQD), so an address without
+        // one is an error rather than the address echoed back.
+        dns_lookup::lookup_addr(&ip).map(Some)
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::*;
+
+/// Answers `host-<last octet>` for 10.0.0.0/8 and nothing else, after
+/// `delay`, counting calls and the most that ran at once.
+#[derive(Default)]
+struct Fake {
+    delay: Duration,
+    calls: AtomicUsize,
+    running: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Resolver for Arc<Fake> {
+    fn reverse(&self, ip: IpAddr) -> io::Result<Option<String>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        thread::sleep(self.delay);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        match ip {
+            IpAddr::V4(v4) if v4.octets()[0] == 10 => Ok(Some(format!("host-{}", v4.octets()[3]))),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn ip(text: &str) -> IpAddr {
+    text.parse().unwrap()
+}
+
+fn dns(fake: &Arc<Fake>, config: DnsConfig) -> ReverseDns {
+    ReverseDns::new(fake.clone(), HostsFile::default(), config)
+}
+
+/// Polls `lookup` until it answers, for up to two seconds.
+fn resolved(dns: &ReverseDns, address: &str) -> Option<String> {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        let name = dns.lookup(ip(address));
+        if name.is_some() || Instant::now() > deadline {
+            return name;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn hosts_file_first() {
+    let fake = Arc::new(Fake::default());
+    let hosts = HostsFile::parse(
+        "# comment\n127.0.0.1 localhost\n10.0.0.7\tbuild-7 build-7.lan # the CI box\n::1 ip6-localhost\n",
+    );
+    let dns = ReverseDns::new(fake.clone(), hosts, DnsConfig::default());
+
+    assert_eq!(dns.lookup(ip("10.0.0.7")).as_deref(), Some("build-7"));
+    assert_eq!(dns.lookup(ip("::1")).as_deref(), Some("ip6-localhost"));
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn resolves_in_the_background_once() {
+    let fake = Arc::new(Fake {
+        delay: Duration::from_millis(50),
+        ..Fake::default()
+    });
+    let dns = dns(&fake, DnsConfig::default());
+
+    assert_eq!(dns.lookup(ip("10.0.0.1")), None, "a miss does not block");
+    assert_eq!(resolved(&dns, "10.0.0.1").as_deref(), Some("host-1"));
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn negative_answers_are_cached() {
+    let fake = Arc::new(Fake::default());
+    let dns = dns(&fake, DnsConfig::default());
+
+    dns.lookup(ip("192.0.2.1"));
+    thread::sleep(Duration::from_millis(100));
+    for _ in 0..10 {
+        assert_eq!(dns.lookup(ip("192.0.2.1")), None);
+    }
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn expired_names_are_served_while_refreshed() {
+    let fake = Arc::new(Fake::default());
+    let dns = dns(
+        &fake,
+        DnsConfig {
+            ttl: Duration::ZERO,
+            ..DnsConfig::default()
+        },
+    );
+
+    assert_eq!(resolved(&dns, "10.0.0.2").as_deref(), Some("host-2"));
+    assert_eq!(dns.lookup(ip("10.0.0.2")).as_deref(), Some("host-2"));
+    thread::sleep(Duration::from_millis(100));
+    assert!(fake.calls.load(Ordering::SeqCst) >= 2);
+}
+
+#[test]
+fn slow_lookups_time_out() {
+    let fake = Arc::new(Fake {
+        delay: Duration::from_millis(300),
+        ..Fake::default()
+    });
+    let dns = dns(
+        &fake,
+        DnsConfig {
+            timeout: Duration::from_millis(20),
+            negative_ttl: Duration::from_secs(60),
+            ..DnsConfig::default()
+        },
+    );
+
+    dns.lookup(ip("10.0.0.3"));
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(dns.lookup(ip("10.0.0.3")), None, "timed out");
+    // The late answer still lands in the cache.
+    assert_eq!(resolved(&dns, "10.0.0.3").as_deref(), Some("host-3"));
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn concurrency_is_limited() {
+    let fake = Arc::new(Fake {
+        delay: Duration::from_millis(20),
+        ..Fake::default()
+    });
+    let dns = dns(
+        &fake,
+        DnsConfig {
+            max_concurrent: 2,
+            ..DnsConfig::default()
+        },
+    );
+
+    for n in 1..=12 {
+        dns.lookup(ip(&format!("10.0.0.{n}")));
+    }
+    for n in 1..=12 {
+        assert_eq!(
+            resolved(&dns, &format!("10.0.0.{n}")),
+            Some(format!("host-{n}"))
+        );
+    }
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 12);
+    assert!(fake.peak.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+fn least_recently_used_are_evicted() {
+    let fake = Arc::new(Fake::default());
+    let dns = dns(
+        &fake,
+        DnsConfig {
+            capacity: 2,
+            ..DnsConfig::default()
+        },
+    );
+
+    resolved(&dns, "10.0.0.1");
+    resolved(&dns, "10.0.0.2");
+    dns.lookup(ip("10.0.0.1"));
+    resolved(&dns, "10.0.0.3");
+
+    // 10.0.0.2 was evicted and is looked up again; 10.0.0.1 was not.
+    let calls = fake.calls.load(Ordering::SeqCst);
+    assert_eq!(dns.lookup(ip("10.0.0.1")).as_deref(), Some("host-1"));
+    assert_eq!(dns.lookup(ip("10.0.0.2")), None);
+    assert_eq!(resolved(&dns, "10.0.0.2").as_deref(), Some("host-2"));
+    assert_eq!(fake.calls.load(Ordering::SeqCst), calls + 1);
+}
+
+#[test]
+fn annotates_remote_addresses() {
+    let fake = Arc::new(Fake::default());
+    let hosts = HostsFile::parse("10.0.0.9 db\n");
+    let dns = ReverseDns::new(fake, hosts, DnsConfig::default());
+    let row = |remote: Option<&str>| ProcessInfo {
+        protocol: "tcp".into(),
+        remote: crate::process_info::AddressPort {
+            address: remote.map(String::from),
+            port: Some(5432),
+            hostname: None,
+        },
+        ..ProcessInfo::default()
+    };
+    let mut rows = vec![row(Some("10.0.0.9")), row(None)];
+
+    dns.annotate(&mut rows);
+    assert_eq!(rows[0].remote.hostname.as_deref(), Some("db"));
+    assert_eq!(rows[1].remote.hostname, None);
+
+    dns.set_enabled(false);
+    rows[0].remote.hostname = None;
+    dns.annotate(&mut rows);
+    assert_eq!(rows[0].remote.hostname, None);
+}
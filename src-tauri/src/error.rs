@@ -0,0 +1,183 @@
+use std::fmt;
+use std::io;
+
+use serde::ser::{Serialize, Serializer};
+
+use crate::query::{QueryError, Span};
+
+/// Error returned by every command. It reaches the frontend as
+/// `{ kind, message, errno, pid, span }` so the UI can branch on `kind`
+/// (or on `errno`, e.g. to offer elevation on `EPERM`) instead of parsing
+/// the message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetstatCatError {
+    /// No socket source could enumerate the sockets.
+    SocketEnumeration {
+        message: String,
+        errno: Option<i32>,
+    },
+    /// The process exited (or never existed).
+    ProcessNotFound {
+        pid: u32,
+    },
+    /// No open socket matches the given connection (any more).
+    ConnectionNotFound {
+        id: String,
+    },
+    PermissionDenied {
+        pid: Option<u32>,
+        errno: Option<i32>,
+    },
+    /// The protection policy covers this process; retry with `force`.
+    Protected {
+        pid: u32,
+        reason: String,
+    },
+    /// Signalling a process failed for another reason.
+    Signal {
+        pid: u32,
+        errno: Option<i32>,
+    },
+    /// Any other failed system call.
+    Io {
+        message: String,
+        errno: Option<i32>,
+    },
+    /// Reading or writing the history database failed.
+    Database(String),
+    InvalidFilter(QueryError),
+    InvalidArgument(String),
+    /// The operation is not available on this platform.
+    Unsupported(String),
+    /// A newer request superseded this one.
+    Cancelled,
+}
+
+impl NetstatCatError {
+    /// Maps an OS error from acting on `pid`.
+    pub fn from_process_io(pid: u32, err: &io::Error) -> Self {
+        let errno = err.raw_os_error();
+        #[cfg(unix)]
+        if errno == Some(libc::ESRCH) {
+            return NetstatCatError::ProcessNotFound { pid };
+        }
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => NetstatCatError::PermissionDenied {
+                pid: Some(pid),
+                errno,
+            },
+            io::ErrorKind::NotFound => NetstatCatError::ProcessNotFound { pid },
+            _ => NetstatCatError::Signal { pid, errno },
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NetstatCatError::SocketEnumeration { .. } => "socketEnumeration",
+            NetstatCatError::ProcessNotFound { .. } => "processNotFound",
+            NetstatCatError::ConnectionNotFound { .. } => "connectionNotFound",
+            NetstatCatError::PermissionDenied { .. } => "permissionDenied",
+            NetstatCatError::Protected { .. } => "protected",
+            NetstatCatError::Signal { .. } => "signal",
+            NetstatCatError::Io { .. } => "io",
+            NetstatCatError::Database(_) => "database",
+            NetstatCatError::InvalidFilter(_) => "invalidFilter",
+            NetstatCatError::InvalidArgument(_) => "invalidArgument",
+            NetstatCatError::Unsupported(_) => "unsupported",
+            NetstatCatError::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn errno(&self) -> Option<i32> {
+        match self {
+            NetstatCatError::SocketEnumeration { errno, .. }
+            | NetstatCatError::PermissionDenied { errno, .. }
+            | NetstatCatError::Signal { errno, .. }
+            | NetstatCatError::Io { errno, .. } => *errno,
+            _ => None,
+        }
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        match self {
+            NetstatCatError::ProcessNotFound { pid }
+            | NetstatCatError::Protected { pid, .. }
+            | NetstatCatError::Signal { pid, .. } => Some(*pid),
+            NetstatCatError::PermissionDenied { pid, .. } => *pid,
+            _ => None,
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            NetstatCatError::InvalidFilter(err) => Some(err.span),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NetstatCatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetstatCatError::SocketEnumeration { message, .. }
+            | NetstatCatError::Io { message, .. } => f.write_str(message),
+            NetstatCatError::ProcessNotFound { pid } => {
+                write!(f, "Process with PID {pid} not found")
+            }
+            NetstatCatError::ConnectionNotFound { id } => {
+                write!(f, "Connection {id} no longer exists")
+            }
+            NetstatCatError::PermissionDenied { pid: Some(pid), .. } => {
+                write!(f, "Permission denied for process with PID {pid}")
+            }
+            NetstatCatError::PermissionDenied { pid: None, .. } => f.write_str("Permission denied"),
+            NetstatCatError::Protected { pid, reason } => {
+                write!(f, "Process with PID {pid} is protected: {reason}")
+            }
+            NetstatCatError::Signal { pid, errno } => {
+                write!(f, "Failed to signal process with PID {pid}")?;
+                match errno {
+                    Some(errno) => write!(f, ": {}", io::Error::from_raw_os_error(*errno)),
+                    None => Ok(()),
+                }
+            }
+            NetstatCatError::Database(message) => write!(f, "History database: {message}"),
+            NetstatCatError::InvalidFilter(err) => write!(f, "Invalid filter: {}", err.message),
+            NetstatCatError::InvalidArgument(message) | NetstatCatError::Unsupported(message) => {
+                f.write_str(message)
+            }
+            NetstatCatError::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for NetstatCatError {}
+
+impl From<QueryError> for NetstatCatError {
+    fn from(err: QueryError) -> Self {
+        NetstatCatError::InvalidFilter(err)
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload {
+    kind: &'static str,
+    message: String,
+    errno: Option<i32>,
+    pid: Option<u32>,
+    span: Option<Span>,
+}
+
+impl Serialize for NetstatCatError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Payload {
+            kind: self.kind(),
+            message: self.to_string(),
+            errno: self.errno(),
+            pid: self.pid(),
+            span: self.span(),
+        }
+        .serialize(serializer)
+    }
+}
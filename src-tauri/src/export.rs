@@ -0,0 +1,496 @@
+//! Writes connection rows to files in a choice of formats and columns.
+//!
+//! Every format renders a cell the same way. A wildcard local address is
+//! written as `0.0.0.0` or `[::]`, as the table shows it and filters match it
+//! (see [`display_address`]); a wildcard remote end is no remote at all (see
+//! [`has_remote`]). Anything missing is left empty, or `null` in JSON. `netstat-cat-cli` writes its CSV, JSON and NDJSON through here.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::process_info::{display_address, has_remote, ProcessInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Csv,
+    /// A pretty-printed array of objects.
+    Json,
+    /// One object per line.
+    Ndjson,
+    /// A GitHub-flavoured Markdown table.
+    Markdown,
+    /// A standalone HTML page with one table.
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Column {
+    Id,
+    Protocol,
+    LocalAddress,
+    LocalPort,
+    RemoteAddress,
+    RemotePort,
+    RemoteHostname,
+    State,
+    Pid,
+    ProcessName,
+    Pids,
+    ProcessNames,
+    ProcessState,
+    Uid,
+    Inode,
+    FirstSeen,
+    Age,
+}
+
+/// What the table shows.
+pub const DEFAULT_COLUMNS: [Column; 8] = [
+    Column::Protocol,
+    Column::LocalAddress,
+    Column::LocalPort,
+    Column::RemoteAddress,
+    Column::RemotePort,
+    Column::State,
+    Column::Pid,
+    Column::ProcessName,
+];
+
+impl Column {
+    /// JSON key, same as the row field.
+    fn key(self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Protocol => "protocol",
+            Column::LocalAddress => "localAddress",
+            Column::LocalPort => "localPort",
+            Column::RemoteAddress => "remoteAddress",
+            Column::RemotePort => "remotePort",
+            Column::RemoteHostname => "remoteHostname",
+            Column::State => "state",
+            Column::Pid => "pid",
+            Column::ProcessName => "processName",
+            Column::Pids => "pids",
+            Column::ProcessNames => "processNames",
+            Column::ProcessState => "processState",
+            Column::Uid => "uid",
+            Column::Inode => "inode",
+            Column::FirstSeen => "firstSeen",
+            Column::Age => "age",
+        }
+    }
+
+    /// CSV header: the key in snake case.
+    fn csv_name(self) -> String {
+        let mut name = String::new();
+        for c in self.key().chars() {
+            if c.is_ascii_uppercase() {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        }
+        name
+    }
+
+    /// Header for Markdown and HTML.
+    fn label(self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Protocol => "Protocol",
+            Column::LocalAddress => "Local Address",
+            Column::LocalPort => "Local Port",
+            Column::RemoteAddress => "Remote Address",
+            Column::RemotePort => "Remote Port",
+            Column::RemoteHostname => "Remote Host",
+            Column::State => "State",
+            Column::Pid => "PID",
+            Column::ProcessName => "Process",
+            Column::Pids => "PIDs",
+            Column::ProcessNames => "Processes",
+            Column::ProcessState => "Process State",
+            Column::Uid => "UID",
+            Column::Inode => "Inode",
+            Column::FirstSeen => "First Seen",
+            Column::Age => "Age (ms)",
+        }
+    }
+
+    fn value(self, row: &ProcessInfo) -> Value {
+        let number = |n: Option<u64>| n.map_or(Value::Null, Value::from);
+        match self {
+            Column::Id => row.id.clone().into(),
+            Column::Protocol => row.protocol.clone().into(),
+            Column::LocalAddress => {
+                display_address(&row.protocol, row.local.address.as_deref()).into()
+            }
+            Column::LocalPort => number(row.local.port.map(u64::from)),
+            Column::RemoteAddress if has_remote(row) => {
+                display_address(&row.protocol, row.remote.address.as_deref()).into()
+            }
+            Column::RemotePort if has_remote(row) => number(row.remote.port.map(u64::from)),
+            Column::RemoteAddress | Column::RemotePort => Value::Null,
+            Column::RemoteHostname => row.remote.hostname.clone().into(),
+            Column::State => row.state.clone().into(),
+            // PID 0 means no owner could be seen.
+            Column::Pid => number(Some(u64::from(row.pid)).filter(|&pid| pid != 0)),
+            Column::ProcessName => row.process_name.clone().into(),
+            Column::Pids => row.pids.clone().into(),
+            Column::ProcessNames => row.process_names.clone().into(),
+            Column::ProcessState => serde_json::to_value(row.process_state).unwrap_or(Value::Null),
+            Column::Uid => number(row.uid.map(u64::from)),
+            Column::Inode => number(row.inode.map(u64::from)),
+            Column::FirstSeen => number(row.first_seen),
+            Column::Age => number(row.age),
+        }
+    }
+}
+
+/// A cell for the text formats; lists are joined with `separator`.
+fn text(value: &Value, separator: &str) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| text(item, separator))
+            .collect::<Vec<_>>()
+            .join(separator),
+        other => other.to_string(),
+    }
+}
+
+/// One row as a JSON object with keys in column order.
+pub struct Record<'a> {
+    columns: &'a [Column],
+    row: &'a ProcessInfo,
+}
+
+impl<'a> Record<'a> {
+    pub fn new(row: &'a ProcessInfo, columns: &'a [Column]) -> Self {
+        Record { columns, row }
+    }
+}
+
+impl Serialize for Record<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for &column in self.columns {
+            map.serialize_entry(column.key(), &column.value(self.row))?;
+        }
+        map.end()
+    }
+}
+
+/// Writes `rows` in `format`; no `columns` means [`DEFAULT_COLUMNS`].
+pub fn export(
+    out: &mut impl Write,
+    rows: &[ProcessInfo],
+    format: ExportFormat,
+    columns: &[Column],
+) -> io::Result<()> {
+    let columns = if columns.is_empty() {
+        &DEFAULT_COLUMNS[..]
+    } else {
+        columns
+    };
+    match format {
+        ExportFormat::Csv => {
+            csv_header(out, columns, &[])?;
+            csv_rows(out, rows, columns, &[])
+        }
+        ExportFormat::Json => json(out, rows, columns, true),
+        ExportFormat::Ndjson => {
+            for row in rows {
+                serde_json::to_writer(&mut *out, &Record::new(row, columns))?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        ExportFormat::Markdown => markdown(out, rows, columns),
+        ExportFormat::Html => html(out, rows, columns),
+    }
+}
+
+/// A JSON array of records, on one line unless `pretty`.
+pub fn json(
+    out: &mut impl Write,
+    rows: &[ProcessInfo],
+    columns: &[Column],
+    pretty: bool,
+) -> io::Result<()> {
+    let records: Vec<Record> = rows.iter().map(|row| Record::new(row, columns)).collect();
+    if pretty {
+        serde_json::to_writer_pretty(&mut *out, &records)?;
+    } else {
+        serde_json::to_writer(&mut *out, &records)?;
+    }
+    writeln!(out)
+}
+
+/// The CSV header line. `leading` names extra fields written before the
+/// columns, whose values are passed to [`csv_rows`].
+pub fn csv_header(out: &mut impl Write, columns: &[Column], leading: &[&str]) -> io::Result<()> {
+    let header: Vec<String> = leading
+        .iter()
+        .map(|name| csv_escape(name))
+        .chain(columns.iter().map(|c| c.csv_name()))
+        .collect();
+    writeln!(out, "{}", header.join(","))
+}
+
+/// CSV lines without a header, each starting with the `leading` fields, so
+/// samples can be appended to one file.
+pub fn csv_rows(
+    out: &mut impl Write,
+    rows: &[ProcessInfo],
+    columns: &[Column],
+    leading: &[String],
+) -> io::Result<()> {
+    for row in rows {
+        let fields: Vec<String> = leading
+            .iter()
+            .map(|field| csv_escape(field))
+            .chain(
+                columns
+                    .iter()
+                    .map(|c| csv_escape(&text(&c.value(row), ";"))),
+            )
+            .collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown(out: &mut impl Write, rows: &[ProcessInfo], columns: &[Column]) -> io::Result<()> {
+    // Markdown renderers pass inline HTML through.
+    let escape = |cell: String| {
+        cell.replace('|', "\\|")
+            .replace('<', "&lt;")
+            .replace(['\r', '\n'], " ")
+    };
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+
+    writeln!(
+        out,
+        "{}",
+        line(columns.iter().map(|c| c.label().to_string()).collect())
+    )?;
+    writeln!(
+        out,
+        "{}",
+        line(columns.iter().map(|_| "---".to_string()).collect())
+    )?;
+    for row in rows {
+        let cells = columns
+            .iter()
+            .map(|c| escape(text(&c.value(row), ", ")))
+            .collect();
+        writeln!(out, "{}", line(cells))?;
+    }
+    Ok(())
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html(out: &mut impl Write, rows: &[ProcessInfo], columns: &[Column]) -> io::Result<()> {
+    let mut page = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>netstat-cat connections</title>\n<style>\n\
+         body { font-family: system-ui, sans-serif; font-size: 13px; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { border: 1px solid #ccc; padding: 2px 8px; text-align: left; }\n\
+         th { background: #f3f4f6; }\n\
+         </style>\n</head>\n<body>\n<table>\n<thead>\n<tr>",
+    );
+    for column in columns {
+        let _ = write!(page, "<th>{}</th>", html_escape(column.label()));
+    }
+    page.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in rows {
+        page.push_str("<tr>");
+        for column in columns {
+            let _ = write!(
+                page,
+                "<td>{}</td>",
+                html_escape(&text(&column.value(row), ", "))
+            );
+        }
+        page.push_str("</tr>\n");
+    }
+    page.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+    out.write_all(page.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_info::AddressPort;
+
+    fn row(protocol: &str, local: Option<&str>, process_name: &str) -> ProcessInfo {
+        ProcessInfo {
+            protocol: protocol.into(),
+            local: AddressPort {
+                address: local.map(String::from),
+                port: Some(80),
+                hostname: None,
+            },
+            state: "LISTEN".into(),
+            pid: 7,
+            process_name: process_name.into(),
+            pids: vec![7, 8],
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn render(rows: &[ProcessInfo], format: ExportFormat, columns: &[Column]) -> String {
+        let mut out = Vec::new();
+        export(&mut out, rows, format, columns).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        let rows = [row("tcp", None, r#"say "hi", then quit"#)];
+        let columns = [Column::LocalAddress, Column::ProcessName, Column::Pids];
+        assert_eq!(
+            render(&rows, ExportFormat::Csv, &columns),
+            "local_address,process_name,pids\n0.0.0.0,\"say \"\"hi\"\", then quit\",7;8\n"
+        );
+    }
+
+    #[test]
+    fn csv_leading_fields() {
+        let mut out = Vec::new();
+        csv_header(&mut out, &[Column::Pid], &["sampled_at"]).unwrap();
+        csv_rows(
+            &mut out,
+            &[row("tcp", None, "a")],
+            &[Column::Pid],
+            &["5".into()],
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "sampled_at,pid\n5,7\n");
+    }
+
+    #[test]
+    fn wildcards_render_like_the_table() {
+        let rows = [
+            row("tcp", None, "a"),
+            row("tcp6", None, "b"),
+            row("tcp", Some("127.0.0.1"), "c"),
+            row("unix", None, "d"),
+        ];
+        assert_eq!(
+            render(&rows, ExportFormat::Ndjson, &[Column::LocalAddress]),
+            "{\"localAddress\":\"0.0.0.0\"}\n\
+             {\"localAddress\":\"[::]\"}\n\
+             {\"localAddress\":\"127.0.0.1\"}\n\
+             {\"localAddress\":null}\n"
+        );
+    }
+
+    #[test]
+    fn wildcard_remotes_are_empty() {
+        let listener = ProcessInfo {
+            remote: AddressPort {
+                address: None,
+                port: Some(0),
+                hostname: None,
+            },
+            ..row("tcp", None, "a")
+        };
+        let client = ProcessInfo {
+            remote: AddressPort {
+                address: None,
+                port: Some(443),
+                hostname: None,
+            },
+            ..row("tcp6", Some("::1"), "b")
+        };
+        let columns = [Column::RemoteAddress, Column::RemotePort];
+        assert_eq!(
+            render(&[listener, client], ExportFormat::Csv, &columns),
+            "remote_address,remote_port
+,
+[::],443
+"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_cells() {
+        let rows = [row("tcp", None, "a|b <script>\nnext")];
+        assert_eq!(
+            render(
+                &rows,
+                ExportFormat::Markdown,
+                &[Column::Pid, Column::ProcessName]
+            ),
+            "| PID | Process |\n| --- | --- |\n| 7 | a\\|b &lt;script> next |\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_cells() {
+        let rows = [row("tcp", None, r#"<b>"Tom" & 'Jerry'</b>"#)];
+        let page = render(&rows, ExportFormat::Html, &[Column::ProcessName]);
+        assert!(page.contains("<th>Process</th>"));
+        assert!(page.contains("<td>&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;</td>"));
+        assert!(!page.contains("<b>"));
+    }
+
+    #[test]
+    fn json_keeps_column_order() {
+        let rows = [row("udp", Some("10.0.0.1"), "dns")];
+        let columns = [Column::Pid, Column::Protocol, Column::RemotePort];
+        assert_eq!(
+            render(&rows, ExportFormat::Json, &columns),
+            "[\n  {\n    \"pid\": 7,\n    \"protocol\": \"udp\",\n    \"remotePort\": null\n  }\n]\n"
+        );
+        let mut out = Vec::new();
+        json(&mut out, &rows, &columns, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[{\"pid\":7,\"protocol\":\"udp\",\"remotePort\":null}]\n"
+        );
+    }
+}
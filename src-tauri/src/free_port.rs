@@ -0,0 +1,170 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::NetstatCatError;
+use crate::netstat;
+use crate::process_control::{self, KillOptions, KillReport};
+use crate::process_info::ProcessInfo;
+use crate::process_table::ProcessTable;
+use crate::protection::Guard;
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const POLL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortProtocol {
+    Tcp,
+    Udp,
+}
+
+impl PortProtocol {
+    pub fn parse(name: &str) -> Result<Self, NetstatCatError> {
+        match name.to_ascii_lowercase().as_str() {
+            "tcp" => Ok(PortProtocol::Tcp),
+            "udp" => Ok(PortProtocol::Udp),
+            _ => Err(NetstatCatError::InvalidArgument(format!(
+                "Unknown protocol '{name}', expected tcp or udp"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FreePortOptions {
+    /// `None` frees the port for both TCP and UDP.
+    pub protocol: Option<PortProtocol>,
+    /// `None` only reports who holds the port.
+    pub kill: Option<KillOptions>,
+    /// How long to wait for the port to be released after signalling.
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortHolder {
+    pub pid: u32,
+    pub process_name: String,
+    /// Protocols of the sockets it holds on the port (`tcp`, `udp6`, ...).
+    pub protocols: Vec<String>,
+    pub report: Option<KillReport>,
+    pub error: Option<NetstatCatError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreePortReport {
+    pub port: u16,
+    pub holders: Vec<PortHolder>,
+    /// No listening TCP or bound UDP socket is left on the port.
+    pub free: bool,
+}
+
+/// TCP listeners and bound UDP sockets on `port`. Established connections
+/// do not keep anyone else from binding it.
+fn holds_port(row: &ProcessInfo, port: u16, protocol: Option<PortProtocol>) -> bool {
+    if row.local.port != Some(port) {
+        return false;
+    }
+    let tcp = row.protocol.starts_with("tcp") && row.state == "LISTEN";
+    let udp = row.protocol.starts_with("udp");
+    match protocol {
+        Some(PortProtocol::Tcp) => tcp,
+        Some(PortProtocol::Udp) => udp,
+        None => tcp || udp,
+    }
+}
+
+/// The processes holding `port`, and whether it is in use at all (sockets
+/// of other users may have no visible owner).
+fn port_holders(
+    port: u16,
+    protocol: Option<PortProtocol>,
+    table: &ProcessTable,
+) -> Result<(Vec<PortHolder>, bool), NetstatCatError> {
+    let mut holders: Vec<PortHolder> = Vec::new();
+    let mut in_use = false;
+    for row in netstat::fetch_process_info_list(table)? {
+        if !holds_port(&row, port, protocol) {
+            continue;
+        }
+        in_use = true;
+        for (&pid, name) in row.pids.iter().zip(&row.process_names) {
+            let holder = match holders.iter_mut().find(|h| h.pid == pid) {
+                Some(holder) => holder,
+                None => {
+                    holders.push(PortHolder {
+                        pid,
+                        process_name: name.clone(),
+                        protocols: Vec::new(),
+                        report: None,
+                        error: None,
+                    });
+                    holders.last_mut().unwrap()
+                }
+            };
+            if !holder.protocols.contains(&row.protocol) {
+                holder.protocols.push(row.protocol.clone());
+            }
+        }
+    }
+    Ok((holders, in_use))
+}
+
+/// Finds whoever holds `port`, optionally signals them, then polls until
+/// the port is released or `options.timeout` passes. Asked to kill, it
+/// fails with `PermissionDenied` if the port is in use by no visible process.
+pub fn free_port(
+    port: u16,
+    options: &FreePortOptions,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Result<FreePortReport, NetstatCatError> {
+    let (mut holders, in_use) = port_holders(port, options.protocol, table)?;
+    let Some(kill) = options.kill.filter(|_| in_use) else {
+        return Ok(FreePortReport {
+            port,
+            holders,
+            free: !in_use,
+        });
+    };
+    // Sockets of other users whose owner we cannot see: there is nobody we
+    // could signal, so waiting for the port would only run into the timeout.
+    if holders.is_empty() {
+        return Err(NetstatCatError::PermissionDenied {
+            pid: None,
+            errno: None,
+        });
+    }
+
+    let pids: Vec<u32> = holders.iter().map(|h| h.pid).collect();
+    for (holder, (report, error)) in holders
+        .iter_mut()
+        .zip(process_control::kill_all(&pids, kill, table, guard))
+    {
+        holder.report = Some(report);
+        holder.error = error;
+    }
+
+    // The process may be gone while the kernel is still tearing its
+    // sockets down, or a supervisor may have restarted it; only a fresh
+    // sample says whether the port is really free.
+    let deadline = Instant::now() + options.timeout;
+    let free = loop {
+        if !port_holders(port, options.protocol, table)?.1 {
+            break true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break false;
+        }
+        thread::sleep((deadline - now).min(POLL));
+    };
+
+    Ok(FreePortReport {
+        port,
+        holders,
+        free,
+    })
+}
@@ -0,0 +1,465 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::changes::ConnectionDiff;
+use crate::error::NetstatCatError;
+use crate::process_info::{AddressPort, ProcessInfo};
+use crate::query::Filter;
+
+pub const DEFAULT_MAX_AGE_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+pub const DEFAULT_MAX_ROWS: u64 = 1_000_000;
+/// Most rows a single query returns, newest first.
+pub const QUERY_LIMIT: usize = 10_000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS connections (
+        rowid INTEGER PRIMARY KEY,
+        id TEXT NOT NULL,
+        protocol TEXT NOT NULL,
+        local_address TEXT,
+        local_port INTEGER,
+        remote_address TEXT,
+        remote_port INTEGER,
+        state TEXT NOT NULL,
+        pid INTEGER NOT NULL,
+        process_name TEXT NOT NULL,
+        uid INTEGER,
+        first_seen INTEGER NOT NULL,
+        closed_at INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS connections_open ON connections (id) WHERE closed_at IS NULL;
+    CREATE INDEX IF NOT EXISTS connections_first_seen ON connections (first_seen);
+    CREATE INDEX IF NOT EXISTS connections_closed_at ON connections (closed_at);
+    CREATE TABLE IF NOT EXISTS recorder (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+";
+
+/// How much history to keep. Only closed connections are pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Retention {
+    pub max_age_ms: Option<u64>,
+    pub max_rows: Option<u64>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            max_age_ms: Some(DEFAULT_MAX_AGE_MS),
+            max_rows: Some(DEFAULT_MAX_ROWS),
+        }
+    }
+}
+
+/// Unix times in milliseconds; open ends are unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeRange {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// One connection's lifetime as recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// The connection in its last recorded state; `first_seen` is set.
+    #[serde(flatten)]
+    pub connection: ProcessInfo,
+    /// `None` while it is still open (or the recorder stopped first).
+    pub closed_at: Option<u64>,
+}
+
+/// Connection history in a SQLite database.
+pub struct History {
+    db: Mutex<Connection>,
+}
+
+fn db_err(err: rusqlite::Error) -> NetstatCatError {
+    NetstatCatError::Database(err.to_string())
+}
+
+impl History {
+    /// Opens (or creates) the database.
+    pub fn open(path: &Path) -> Result<Self, NetstatCatError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| NetstatCatError::Io {
+                message: format!("Failed to create {}: {e}", dir.display()),
+                errno: e.raw_os_error(),
+            })?;
+        }
+        let db = Connection::open(path).map_err(db_err)?;
+        db.execute_batch(SCHEMA).map_err(db_err)?;
+        let history = History { db: Mutex::new(db) };
+        history.close_dangling()?;
+        Ok(history)
+    }
+
+    /// Closes connections still open from an earlier recording at the last
+    /// time it recorded anything; the next recording starts with all rows
+    /// added again.
+    pub fn close_dangling(&self) -> Result<(), NetstatCatError> {
+        let db = self.db.lock().unwrap();
+        let last: Option<u64> = db
+            .query_row(
+                "SELECT value FROM recorder WHERE key = 'last_sample'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        if let Some(last) = last {
+            db.execute(
+                "UPDATE connections SET closed_at = ?1 WHERE closed_at IS NULL",
+                [last],
+            )
+            .map_err(db_err)?;
+        }
+        Ok(())
+    }
+
+    /// Applies one sample's diff taken at `at`, then prunes.
+    pub fn record(
+        &self,
+        diff: &ConnectionDiff,
+        at: u64,
+        retention: Retention,
+    ) -> Result<(), NetstatCatError> {
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction().map_err(db_err)?;
+        {
+            let mut insert = tx
+                .prepare_cached(
+                    "INSERT INTO connections (id, protocol, local_address, local_port,
+                        remote_address, remote_port, state, pid, process_name, uid, first_seen)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                )
+                .map_err(db_err)?;
+            for row in &diff.added {
+                insert
+                    .execute(params![
+                        row.id,
+                        row.protocol,
+                        row.local.address,
+                        row.local.port,
+                        row.remote.address,
+                        row.remote.port,
+                        row.state,
+                        row.pid,
+                        row.process_name,
+                        row.uid,
+                        row.first_seen.unwrap_or(at),
+                    ])
+                    .map_err(db_err)?;
+            }
+
+            let mut update = tx
+                .prepare_cached(
+                    "UPDATE connections SET state = ?2, pid = ?3, process_name = ?4
+                     WHERE id = ?1 AND closed_at IS NULL",
+                )
+                .map_err(db_err)?;
+            for row in &diff.changed {
+                update
+                    .execute(params![row.id, row.state, row.pid, row.process_name])
+                    .map_err(db_err)?;
+            }
+
+            let mut close = tx
+                .prepare_cached(
+                    "UPDATE connections SET closed_at = ?2 WHERE id = ?1 AND closed_at IS NULL",
+                )
+                .map_err(db_err)?;
+            for id in &diff.removed {
+                close.execute(params![id, at]).map_err(db_err)?;
+            }
+
+            tx.execute(
+                "INSERT OR REPLACE INTO recorder (key, value) VALUES ('last_sample', ?1)",
+                [at],
+            )
+            .map_err(db_err)?;
+            prune(&tx, at, retention)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    /// Connections open at any point in `range` that match `filter`, most
+    /// recently seen first, at most [`QUERY_LIMIT`].
+    pub fn query(
+        &self,
+        filter: &Filter,
+        range: TimeRange,
+    ) -> Result<Vec<HistoryEntry>, NetstatCatError> {
+        let db = self.db.lock().unwrap();
+        let mut select = db
+            .prepare_cached(
+                "SELECT id, protocol, local_address, local_port, remote_address, remote_port,
+                    state, pid, process_name, uid, first_seen, closed_at
+                 FROM connections
+                 WHERE first_seen <= ?2 AND (closed_at IS NULL OR closed_at >= ?1)
+                 ORDER BY first_seen DESC",
+            )
+            .map_err(db_err)?;
+        let rows = select
+            .query_map(
+                params![range.from.unwrap_or(0), range.to.unwrap_or(i64::MAX as u64)],
+                entry,
+            )
+            .map_err(db_err)?;
+
+        let mut entries = Vec::new();
+        for entry in rows {
+            let entry = entry.map_err(db_err)?;
+            // The search box syntax has no SQL translation; filter here.
+            if filter.matches(&entry.connection) {
+                entries.push(entry);
+                if entries.len() == QUERY_LIMIT {
+                    break;
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let pid: u32 = row.get(7)?;
+    let process_name: String = row.get(8)?;
+    Ok(HistoryEntry {
+        connection: ProcessInfo {
+            id: row.get(0)?,
+            protocol: row.get(1)?,
+            local: AddressPort {
+                address: row.get(2)?,
+                port: row.get(3)?,
+                hostname: None,
+            },
+            remote: AddressPort {
+                address: row.get(4)?,
+                port: row.get(5)?,
+                hostname: None,
+            },
+            state: row.get(6)?,
+            pid,
+            pids: vec![pid],
+            process_names: vec![process_name.clone()],
+            process_name,
+            uid: row.get(9)?,
+            first_seen: row.get(10)?,
+            ..ProcessInfo::default()
+        },
+        closed_at: row.get(11)?,
+    })
+}
+
+fn prune(db: &Connection, now: u64, retention: Retention) -> Result<(), NetstatCatError> {
+    if let Some(max_age) = retention.max_age_ms {
+        db.execute(
+            "DELETE FROM connections WHERE closed_at < ?1",
+            [now.saturating_sub(max_age)],
+        )
+        .map_err(db_err)?;
+    }
+    if let Some(max_rows) = retention.max_rows {
+        db.execute(
+            "DELETE FROM connections WHERE closed_at IS NOT NULL AND rowid <= (
+                SELECT rowid FROM connections ORDER BY rowid DESC LIMIT 1 OFFSET ?1
+            )",
+            [max_rows],
+        )
+        .map_err(db_err)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> History {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(SCHEMA).unwrap();
+        History { db: Mutex::new(db) }
+    }
+
+    fn row(id: &str, port: u16, first_seen: u64) -> ProcessInfo {
+        ProcessInfo {
+            id: id.into(),
+            protocol: "tcp".into(),
+            local: AddressPort {
+                address: Some("127.0.0.1".into()),
+                port: Some(port),
+                hostname: None,
+            },
+            state: "LISTEN".into(),
+            pid: 42,
+            process_name: "server".into(),
+            first_seen: Some(first_seen),
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn diff(
+        added: Vec<ProcessInfo>,
+        changed: Vec<ProcessInfo>,
+        removed: &[&str],
+    ) -> ConnectionDiff {
+        ConnectionDiff {
+            sequence: 0,
+            added,
+            changed,
+            removed: removed.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    const KEEP_ALL: Retention = Retention {
+        max_age_ms: None,
+        max_rows: None,
+    };
+
+    fn all(history: &History, from: Option<u64>, to: Option<u64>) -> Vec<(String, Option<u64>)> {
+        history
+            .query(&Filter::parse("").unwrap(), TimeRange { from, to })
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.connection.id, entry.closed_at))
+            .collect()
+    }
+
+    #[test]
+    fn records_changes_and_closes() {
+        let history = history();
+        history
+            .record(&diff(vec![row("a", 80, 1000)], vec![], &[]), 1000, KEEP_ALL)
+            .unwrap();
+        let mut changed = row("a", 80, 1000);
+        changed.state = "CLOSE_WAIT".into();
+        changed.pid = 43;
+        history
+            .record(&diff(vec![], vec![changed], &[]), 2000, KEEP_ALL)
+            .unwrap();
+        history
+            .record(&diff(vec![], vec![], &["a"]), 3000, KEEP_ALL)
+            .unwrap();
+
+        let entries = history
+            .query(&Filter::parse("").unwrap(), TimeRange::default())
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.connection.state, "CLOSE_WAIT");
+        assert_eq!(entry.connection.pids, vec![43]);
+        assert_eq!(entry.connection.local.port, Some(80));
+        assert_eq!(entry.connection.first_seen, Some(1000));
+        assert_eq!(entry.closed_at, Some(3000));
+    }
+
+    #[test]
+    fn dangling_rows_close_at_the_last_sample() {
+        let history = history();
+        history
+            .record(&diff(vec![row("a", 80, 1000)], vec![], &[]), 1000, KEEP_ALL)
+            .unwrap();
+        history
+            .record(&diff(vec![], vec![], &[]), 2000, KEEP_ALL)
+            .unwrap();
+        assert_eq!(all(&history, None, None), vec![("a".to_string(), None)]);
+
+        history.close_dangling().unwrap();
+        assert_eq!(
+            all(&history, None, None),
+            vec![("a".to_string(), Some(2000))]
+        );
+    }
+
+    #[test]
+    fn prunes_closed_rows_beyond_the_limit() {
+        let history = history();
+        let rows: Vec<ProcessInfo> = (1..=5)
+            .map(|n| row(&format!("r{n}"), 8000 + n, n as u64))
+            .collect();
+        history
+            .record(&diff(rows, vec![], &[]), 10, KEEP_ALL)
+            .unwrap();
+        history
+            .record(
+                &diff(vec![], vec![], &["r1", "r2", "r3", "r4"]),
+                20,
+                KEEP_ALL,
+            )
+            .unwrap();
+
+        let retention = Retention {
+            max_age_ms: None,
+            max_rows: Some(2),
+        };
+        history
+            .record(&diff(vec![], vec![], &[]), 30, retention)
+            .unwrap();
+        // Open rows are never pruned; they count towards the limit though.
+        assert_eq!(
+            all(&history, None, None),
+            vec![("r5".to_string(), None), ("r4".to_string(), Some(20))]
+        );
+
+        let retention = Retention {
+            max_age_ms: Some(5),
+            max_rows: None,
+        };
+        history
+            .record(&diff(vec![], vec![], &[]), 30, retention)
+            .unwrap();
+        assert_eq!(all(&history, None, None), vec![("r5".to_string(), None)]);
+    }
+
+    #[test]
+    fn queries_connections_open_during_the_range() {
+        let history = history();
+        history
+            .record(
+                &diff(
+                    vec![
+                        row("early", 1, 100),
+                        row("long", 2, 150),
+                        row("open", 3, 300),
+                    ],
+                    vec![],
+                    &[],
+                ),
+                300,
+                KEEP_ALL,
+            )
+            .unwrap();
+        history
+            .record(&diff(vec![], vec![], &["early"]), 200, KEEP_ALL)
+            .unwrap();
+        history
+            .record(&diff(vec![], vec![], &["long"]), 400, KEEP_ALL)
+            .unwrap();
+
+        let ids = |from, to| -> Vec<String> {
+            all(&history, from, to)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+        assert_eq!(ids(None, None), ["open", "long", "early"]);
+        assert_eq!(ids(Some(210), Some(250)), ["long"]);
+        assert_eq!(ids(None, Some(120)), ["early"]);
+        // Closing exactly at `from` still overlaps.
+        assert_eq!(ids(Some(200), Some(200)), ["long", "early"]);
+        assert_eq!(ids(Some(500), None), ["open"]);
+
+        let filtered = history
+            .query(&Filter::parse("lport=2").unwrap(), TimeRange::default())
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].connection.id, "long");
+    }
+}
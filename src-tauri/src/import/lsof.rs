@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::process_info::{AddressPort, ProcessInfo};
+
+use super::{endpoint, normalize_state, row};
+
+/// ```text
+/// COMMAND  PID USER FD  TYPE DEVICE SIZE/OFF NODE NAME
+/// sshd     812 root 3u  IPv4  23456      0t0  TCP *:22 (LISTEN)
+/// chrome  4242 ann  87u IPv4 123456      0t0  TCP 10.0.0.5:51234->142.250.1.1:443 (ESTABLISHED)
+/// ```
+///
+/// `lsof` lists a socket once per process and descriptor; lines for the
+/// same socket become one row with several owners.
+pub fn parse(text: &str) -> Vec<ProcessInfo> {
+    let mut rows: Vec<ProcessInfo> = Vec::new();
+    let mut sockets: HashMap<(String, String), usize> = HashMap::new();
+    for (device, info) in text.lines().filter_map(parse_line) {
+        let key = (
+            device,
+            format!("{}|{:?}|{:?}", info.protocol, info.local, info.remote),
+        );
+        match sockets.get(&key) {
+            Some(&index) => {
+                let row = &mut rows[index];
+                if !row.pids.contains(&info.pid) {
+                    row.pids.push(info.pid);
+                    row.process_names.push(info.process_name);
+                }
+            }
+            None => {
+                sockets.insert(key, rows.len());
+                rows.push(info);
+            }
+        }
+    }
+    rows
+}
+
+/// The row and its `DEVICE` column, which identifies the socket.
+fn parse_line(line: &str) -> Option<(String, ProcessInfo)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    // NODE is the protocol; SIZE/OFF is sometimes left out.
+    let node = tokens
+        .iter()
+        .skip(5)
+        .position(|t| matches!(*t, "TCP" | "UDP"))?
+        + 5;
+    let &[command, pid, user, _fd, kind, device, ..] = tokens.as_slice() else {
+        return None;
+    };
+    let pid: u32 = pid.parse().ok()?;
+    let udp = tokens[node] == "UDP";
+    let v6 = match kind {
+        "IPv4" => false,
+        "IPv6" => true,
+        _ => return None,
+    };
+
+    let (endpoints, state) = match tokens.get(node + 1..)? {
+        [endpoints] => (*endpoints, ""),
+        [endpoints, state, ..] => (
+            *endpoints,
+            state.trim_start_matches('(').trim_end_matches(')'),
+        ),
+        [] => return None,
+    };
+    let (local, remote) = match endpoints.split_once("->") {
+        Some((local, remote)) => (endpoint(local)?, endpoint(remote)?),
+        None if udp => (endpoint(endpoints)?, AddressPort::default()),
+        None => (
+            endpoint(endpoints)?,
+            AddressPort {
+                address: None,
+                port: Some(0),
+                hostname: None,
+            },
+        ),
+    };
+    let protocol = match (udp, v6) {
+        (false, false) => "tcp",
+        (false, true) => "tcp6",
+        (true, false) => "udp",
+        (true, true) => "udp6",
+    };
+
+    // `lsof` escapes spaces in names as `\x20`.
+    let name = command.replace("\\x20", " ");
+    let mut info = row(
+        protocol,
+        local,
+        remote,
+        normalize_state(state),
+        vec![(pid, name)],
+    );
+    info.uid = user.parse().ok();
+    // On Linux the device of a socket is its inode.
+    info.inode = device.parse().ok();
+    Some((device.to_string(), info))
+}
@@ -0,0 +1,205 @@
+//! Turns text dumps of `ss`, `netstat` and `lsof` from other machines into
+//! rows that can be browsed like a snapshot. Only Internet sockets are
+//! read; Unix sockets and anything unrecognised are skipped.
+
+mod lsof;
+mod netstat;
+mod ss;
+#[cfg(test)]
+mod tests;
+
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NetstatCatError;
+use crate::process_info::{connection_id, AddressPort, ProcessInfo, TcpInfo};
+use crate::snapshot::{Snapshot, SnapshotMeta};
+use crate::tracker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// `ss -tanp` (or `-tuanp`, optionally with `-e`).
+    Ss,
+    /// Linux net-tools `netstat -anp`.
+    Netstat,
+    /// `lsof -i -n -P`.
+    Lsof,
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 3] =
+        [ImportFormat::Ss, ImportFormat::Netstat, ImportFormat::Lsof];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Ss => "ss",
+            ImportFormat::Netstat => "netstat",
+            ImportFormat::Lsof => "lsof",
+        }
+    }
+
+    /// Recognises a format by its header line.
+    fn from_header(line: &str) -> Option<ImportFormat> {
+        let mut words = line.split_whitespace();
+        match (words.next()?, words.next()?) {
+            ("State" | "Netid", _) if line.contains("Recv-Q") => Some(ImportFormat::Ss),
+            ("Proto", "Recv-Q") | ("Active", "Internet") => Some(ImportFormat::Netstat),
+            ("COMMAND", "PID") => Some(ImportFormat::Lsof),
+            _ => None,
+        }
+    }
+}
+
+/// The format of `text`: by its first header line, or else whichever parser
+/// understands the most lines.
+pub fn detect(text: &str) -> Option<ImportFormat> {
+    text.lines()
+        .find(|line| !line.trim().is_empty())
+        .and_then(ImportFormat::from_header)
+        .or_else(|| {
+            ImportFormat::ALL
+                .into_iter()
+                .map(|format| (parse(text, format).len(), format))
+                .filter(|&(rows, _)| rows > 0)
+                .max_by_key(|&(rows, _)| rows)
+                .map(|(_, format)| format)
+        })
+}
+
+pub fn parse(text: &str, format: ImportFormat) -> Vec<ProcessInfo> {
+    let mut rows = match format {
+        ImportFormat::Ss => ss::parse(text),
+        ImportFormat::Netstat => netstat::parse(text),
+        ImportFormat::Lsof => lsof::parse(text),
+    };
+    for row in &mut rows {
+        row.id = connection_id(row);
+    }
+    rows
+}
+
+/// Reads a dump into a read-only snapshot, detecting the format unless
+/// given. The snapshot is dated by the file's modification time.
+pub fn import(path: &Path, format: Option<ImportFormat>) -> Result<Snapshot, NetstatCatError> {
+    let io_err = |err: std::io::Error| NetstatCatError::Io {
+        message: format!("{}: {err}", path.display()),
+        errno: err.raw_os_error(),
+    };
+    let raw = std::fs::read(path).map_err(io_err)?;
+    let text = String::from_utf8_lossy(&raw);
+    let taken_at = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or_else(tracker::now_ms, |since| since.as_millis() as u64);
+
+    let format = format.or_else(|| detect(&text)).ok_or_else(|| {
+        NetstatCatError::InvalidArgument(format!(
+            "{} does not look like ss, netstat or lsof output",
+            path.display()
+        ))
+    })?;
+    let connections = parse(&text, format);
+    if connections.is_empty() {
+        return Err(NetstatCatError::InvalidArgument(format!(
+            "No connections found in {} as {} output",
+            path.display(),
+            format.name()
+        )));
+    }
+
+    Ok(Snapshot {
+        meta: SnapshotMeta {
+            hostname: None,
+            os: None,
+            kernel: None,
+            taken_at,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            source: Some(format.name().to_string()),
+        },
+        connections,
+    })
+}
+
+/// Parses `address:port` as the tools print it: `*` and unspecified
+/// addresses become `None` (as for live rows), IPv6 may be bracketed and
+/// carry a `%scope`, and a `*` port is `None`.
+fn endpoint(text: &str) -> Option<AddressPort> {
+    let (address, port) = text.rsplit_once(':')?;
+    let port = match port {
+        "*" => None,
+        port => Some(port.parse().ok()?),
+    };
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    let address = address.split_once('%').map_or(address, |(ip, _scope)| ip);
+    let address = match address.parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => None,
+        Ok(ip) => Some(ip.to_string()),
+        Err(_) if address == "*" || address.is_empty() => None,
+        // A host name from a dump taken without `-n`.
+        Err(_) => Some(address.to_string()),
+    };
+    Some(AddressPort {
+        address,
+        port,
+        hostname: None,
+    })
+}
+
+/// Whether an endpoint as printed is IPv6 (`[::1]:80`, `:::22`).
+fn is_v6(text: &str) -> bool {
+    text.starts_with('[') || text.matches(':').count() > 1
+}
+
+/// States in the spelling used for live rows.
+fn normalize_state(state: &str) -> String {
+    match state.to_ascii_uppercase().replace('-', "_").as_str() {
+        "ESTAB" => "ESTABLISHED".to_string(),
+        "SYN_RECV" => "SYN_RECEIVED".to_string(),
+        "CLOSE" => "CLOSED".to_string(),
+        "UNCONN" => String::new(),
+        state => state.to_string(),
+    }
+}
+
+/// Assembles a row; the first owner is the primary one.
+fn row(
+    protocol: &str,
+    local: AddressPort,
+    remote: AddressPort,
+    state: String,
+    owners: Vec<(u32, String)>,
+) -> ProcessInfo {
+    let mut pids = Vec::with_capacity(owners.len());
+    let mut process_names = Vec::with_capacity(owners.len());
+    for (pid, name) in owners {
+        if !pids.contains(&pid) {
+            pids.push(pid);
+            process_names.push(name);
+        }
+    }
+    ProcessInfo {
+        protocol: protocol.to_string(),
+        local,
+        remote,
+        state,
+        pid: pids.first().copied().unwrap_or(0),
+        process_name: process_names.first().cloned().unwrap_or_default(),
+        pids,
+        process_names,
+        ..ProcessInfo::default()
+    }
+}
+
+/// Queue sizes for TCP rows, like the live `/proc` fallback reports.
+fn queues(recv_q: &str, send_q: &str) -> Option<TcpInfo> {
+    Some(TcpInfo {
+        recv_q: recv_q.parse().ok()?,
+        send_q: send_q.parse().ok()?,
+        ..TcpInfo::default()
+    })
+}
@@ -0,0 +1,54 @@
+use crate::process_info::{AddressPort, ProcessInfo};
+
+use super::{endpoint, normalize_state, queues, row};
+
+/// ```text
+/// Proto Recv-Q Send-Q Local Address  Foreign Address  State   PID/Program name
+/// tcp        0      0 0.0.0.0:22     0.0.0.0:*        LISTEN  812/sshd: /usr/sbin
+/// udp        0      0 0.0.0.0:68     0.0.0.0:*                700/dhclient
+/// ```
+pub fn parse(text: &str) -> Vec<ProcessInfo> {
+    text.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<ProcessInfo> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let &[proto @ ("tcp" | "tcp6" | "udp" | "udp6"), recv_q, send_q, local, remote, ref rest @ ..] =
+        tokens.as_slice()
+    else {
+        return None;
+    };
+    recv_q.parse::<u32>().ok()?;
+    send_q.parse::<u32>().ok()?;
+    let udp = proto.starts_with("udp");
+    let local = endpoint(local)?;
+    let mut remote = endpoint(remote)?;
+
+    // UDP sockets usually have no state, leaving the program in its place.
+    let (state, program) = match rest {
+        [state, program @ ..] if state.chars().all(|c| c.is_ascii_uppercase() || c == '_') => {
+            (normalize_state(state), program)
+        }
+        program => (String::new(), program),
+    };
+    // "812/sshd: /usr/sbin"; "-" when the owner was not visible.
+    let program = program.join(" ");
+    let owners = program
+        .split_once('/')
+        .and_then(|(pid, name)| Some((pid.parse().ok()?, name.trim().to_string())))
+        .into_iter()
+        .collect();
+
+    if udp {
+        if remote.address.is_none() {
+            remote = AddressPort::default();
+        }
+    } else if remote.port.is_none() {
+        remote.port = Some(0);
+    }
+    let mut info = row(proto, local, remote, state, owners);
+    if !udp {
+        info.tcp_info = queues(recv_q, send_q);
+    }
+    Some(info)
+}
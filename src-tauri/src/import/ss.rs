@@ -0,0 +1,95 @@
+use crate::process_info::{AddressPort, ProcessInfo};
+
+use super::{endpoint, is_v6, normalize_state, queues, row};
+
+/// ```text
+/// State  Recv-Q Send-Q Local Address:Port Peer Address:Port Process
+/// LISTEN 0      511    0.0.0.0:80         0.0.0.0:*         users:(("nginx",pid=1,fd=6),("nginx",pid=2,fd=6))
+/// ```
+///
+/// A leading `Netid` column appears when several protocols were asked for.
+/// Only its `tcp` and `udp` rows are imported.
+pub fn parse(text: &str) -> Vec<ProcessInfo> {
+    text.lines().filter_map(parse_line).collect()
+}
+
+/// Every `Netid` ss prints, so that one is never mistaken for a state.
+const NETIDS: &[&str] = &[
+    "tcp", "udp", "raw", "icmp6", "mptcp", "dccp", "sctp", "u_str", "u_dgr", "u_seq", "p_raw",
+    "p_dgr", "nl", "v_str", "v_dgr", "xdp", "tipc", "???",
+];
+
+fn parse_line(line: &str) -> Option<ProcessInfo> {
+    // Process names may contain spaces, so split the columns off first.
+    let (columns, users) = match line.find("users:(") {
+        Some(at) => (&line[..at], Some(&line[at..])),
+        None => (line, None),
+    };
+    let mut tokens: Vec<&str> = columns.split_whitespace().collect();
+
+    let netid = match *tokens.first()? {
+        "tcp" | "udp" => Some(tokens.remove(0)),
+        first if NETIDS.contains(&first) => return None,
+        _ => None,
+    };
+    let &[state, recv_q, send_q, local, remote, ..] = tokens.as_slice() else {
+        return None;
+    };
+    let udp = match netid {
+        Some(netid) => netid == "udp",
+        None => state == "UNCONN",
+    };
+    let local_address = endpoint(local)?;
+    let mut remote_address = endpoint(remote)?;
+    let protocol = match (udp, is_v6(local)) {
+        (false, false) => "tcp",
+        (false, true) => "tcp6",
+        (true, false) => "udp",
+        (true, true) => "udp6",
+    };
+
+    let owners = users.map(owners).unwrap_or_default();
+    if udp {
+        if remote_address.address.is_none() {
+            remote_address = AddressPort::default();
+        }
+    } else if remote_address.port.is_none() {
+        // Live TCP listeners report the unbound peer port as 0.
+        remote_address.port = Some(0);
+    }
+    let mut info = row(
+        protocol,
+        local_address,
+        remote_address,
+        normalize_state(state),
+        owners,
+    );
+    if !udp {
+        info.tcp_info = queues(recv_q, send_q);
+    }
+    // Extra columns from `-e`, after the process if there is one.
+    let extra = users.into_iter().flat_map(str::split_whitespace);
+    for token in tokens[5..].iter().copied().chain(extra) {
+        if let Some(uid) = token.strip_prefix("uid:") {
+            info.uid = uid.parse().ok();
+        } else if let Some(inode) = token.strip_prefix("ino:") {
+            info.inode = inode.parse().ok();
+        }
+    }
+    Some(info)
+}
+
+/// `users:(("nginx",pid=1234,fd=6),("nginx",pid=1235,fd=6))`.
+fn owners(users: &str) -> Vec<(u32, String)> {
+    users
+        .split("(\"")
+        .skip(1)
+        .filter_map(|entry| {
+            let (name, rest) = entry.split_once("\",pid=")?;
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            Some((rest[..digits].parse().ok()?, name.to_string()))
+        })
+        .collect()
+}
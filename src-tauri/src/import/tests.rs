@@ -0,0 +1,198 @@
+use super::*;
+
+const SS: &str = r#"State  Recv-Q Send-Q  Local Address:Port   Peer Address:Port Process
+LISTEN 0      511           0.0.0.0:80          0.0.0.0:*     users:(("nginx",pid=1234,fd=6),("nginx",pid=1235,fd=6))
+LISTEN 0      128              [::]:22             [::]:*     users:(("sshd",pid=812,fd=4))
+ESTAB  0      36           10.0.0.5:22         10.0.0.9:51234 users:(("sshd: ann [priv]",pid=4100,fd=4))
+TIME-WAIT 0   0           127.0.0.1:40000     127.0.0.1:8080
+"#;
+
+const SS_NETID: &str = "Netid State  Recv-Q Send-Q Local Address:Port Peer Address:Port Process
+udp   UNCONN 0      0      127.0.0.53%lo:53        0.0.0.0:*     users:((\"systemd-resolve\",pid=600,fd=13)) uid:991 ino:20315 sk:1
+tcp   LISTEN 0      4096       127.0.0.1:631       0.0.0.0:*     ino:18000 sk:2
+u_str ESTAB  0      0      /run/dbus/system_bus_socket 19501 * 19500 users:((\"dbus\",pid=500,fd=12))
+";
+
+const SS_ALL: &str = r#"Netid State  Recv-Q Send-Q Local Address:Port Peer Address:Port Process
+nl    UNCONN 0      0               rtnl:NetworkManager/700 *
+p_raw UNCONN 0      0                    *:wlan0           *    users:(("wpa_supplicant",pid=650,fd=12))
+icmp6 UNCONN 0      0                    *:58              *:*  users:(("NetworkManager",pid=700,fd=20))
+raw   UNCONN 0      0              0.0.0.0:1         0.0.0.0:*  users:(("ping",pid=900,fd=3))
+mptcp LISTEN 0      4096           0.0.0.0:8443      0.0.0.0:*  users:(("mptcpd",pid=910,fd=4))
+u_seq LISTEN 0      4096  /run/udev/control 16093           * 0 users:(("systemd",pid=1,fd=40))
+tcp   ESTAB  0      0            10.0.0.5:22        10.0.0.9:51234 users:(("sshd",pid=4100,fd=4))
+udp   UNCONN 0      0            0.0.0.0:68          0.0.0.0:*  users:(("dhclient",pid=620,fd=6))
+"#;
+
+const NETSTAT: &str = "Active Internet connections (servers and established)
+Proto Recv-Q Send-Q Local Address           Foreign Address         State       PID/Program name
+tcp        0      0 0.0.0.0:22              0.0.0.0:*               LISTEN      812/sshd: /usr/sbin
+tcp6       0      0 :::80                   :::*                    LISTEN      1234/nginx: master
+tcp        0      0 10.0.0.5:22             10.0.0.9:51234          ESTABLISHED -
+udp        0      0 0.0.0.0:68              0.0.0.0:*                           700/dhclient
+Active UNIX domain sockets (servers and established)
+Proto RefCnt Flags       Type       State         I-Node   PID/Program name     Path
+unix  2      [ ACC ]     STREAM     LISTENING     12345    1/systemd            /run/systemd/private
+";
+
+const LSOF: &str = r"COMMAND     PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+nginx      1234 root    6u  IPv4  30001      0t0  TCP *:80 (LISTEN)
+nginx      1235 www     6u  IPv4  30001      0t0  TCP *:80 (LISTEN)
+sshd        812 root    4u  IPv6  23458      0t0  TCP *:22 (LISTEN)
+Web\x20Cont 4242 ann   87u  IPv4 123456      0t0  TCP 10.0.0.5:51234->142.250.1.1:443 (ESTABLISHED)
+dhclient    700 root    6u  IPv4  19999      0t0  UDP *:68
+";
+
+fn owners(row: &ProcessInfo) -> Vec<(u32, &str)> {
+    row.pids
+        .iter()
+        .copied()
+        .zip(row.process_names.iter().map(String::as_str))
+        .collect()
+}
+
+#[test]
+fn ss_rows() {
+    let rows = parse(SS, ImportFormat::Ss);
+    assert_eq!(rows.len(), 4);
+
+    assert_eq!(rows[0].protocol, "tcp");
+    assert_eq!(
+        rows[0].local,
+        AddressPort {
+            address: None,
+            port: Some(80),
+            hostname: None,
+        }
+    );
+    assert_eq!(
+        rows[0].remote,
+        AddressPort {
+            address: None,
+            port: Some(0),
+            hostname: None,
+        }
+    );
+    assert_eq!(rows[0].state, "LISTEN");
+    assert_eq!(owners(&rows[0]), [(1234, "nginx"), (1235, "nginx")]);
+    assert_eq!(rows[0].pid, 1234);
+    assert_eq!(rows[0].tcp_info.as_ref().unwrap().send_q, 511);
+
+    assert_eq!(rows[1].protocol, "tcp6");
+    assert_eq!(rows[2].state, "ESTABLISHED");
+    assert_eq!(owners(&rows[2]), [(4100, "sshd: ann [priv]")]);
+    assert_eq!(rows[2].remote.address.as_deref(), Some("10.0.0.9"));
+    assert_eq!(rows[3].state, "TIME_WAIT");
+    assert!(rows[3].pids.is_empty());
+    assert!(rows.iter().all(|row| !row.id.is_empty()));
+}
+
+#[test]
+fn ss_netid_and_extended_columns() {
+    let rows = parse(SS_NETID, ImportFormat::Ss);
+    assert_eq!(rows.len(), 2, "the Unix socket is skipped");
+
+    assert_eq!(rows[0].protocol, "udp");
+    assert_eq!(rows[0].local.address.as_deref(), Some("127.0.0.53"));
+    assert_eq!(rows[0].remote, AddressPort::default());
+    assert_eq!(rows[0].state, "");
+    assert_eq!(rows[0].uid, Some(991));
+    assert_eq!(rows[0].inode, Some(20315));
+    assert_eq!(rows[0].tcp_info, None);
+
+    assert_eq!(rows[1].inode, Some(18000));
+    assert_eq!(rows[1].pid, 0);
+}
+
+#[test]
+fn ss_skips_other_netids() {
+    let rows = parse(SS_ALL, ImportFormat::Ss);
+    let rows: Vec<(&str, &str, Option<u16>)> = rows
+        .iter()
+        .map(|r| (r.protocol.as_str(), r.state.as_str(), r.local.port))
+        .collect();
+    assert_eq!(
+        rows,
+        [("tcp", "ESTABLISHED", Some(22)), ("udp", "", Some(68))]
+    );
+}
+
+#[test]
+fn netstat_rows() {
+    let rows = parse(NETSTAT, ImportFormat::Netstat);
+    assert_eq!(rows.len(), 4);
+
+    assert_eq!(owners(&rows[0]), [(812, "sshd: /usr/sbin")]);
+    assert_eq!(rows[1].protocol, "tcp6");
+    assert_eq!(
+        rows[1].local,
+        AddressPort {
+            address: None,
+            port: Some(80),
+            hostname: None,
+        }
+    );
+    assert_eq!(rows[2].state, "ESTABLISHED");
+    assert!(rows[2].pids.is_empty());
+    assert_eq!(rows[3].protocol, "udp");
+    assert_eq!(rows[3].state, "");
+    assert_eq!(owners(&rows[3]), [(700, "dhclient")]);
+}
+
+#[test]
+fn lsof_merges_shared_sockets() {
+    let rows = parse(LSOF, ImportFormat::Lsof);
+    assert_eq!(rows.len(), 4);
+
+    assert_eq!(owners(&rows[0]), [(1234, "nginx"), (1235, "nginx")]);
+    assert_eq!(rows[0].inode, Some(30001));
+    assert_eq!(rows[0].state, "LISTEN");
+    assert_eq!(rows[1].protocol, "tcp6");
+    assert_eq!(owners(&rows[2]), [(4242, "Web Cont")]);
+    assert_eq!(rows[2].remote.port, Some(443));
+    assert_eq!(rows[3].protocol, "udp");
+    assert_eq!(rows[3].remote, AddressPort::default());
+}
+
+#[test]
+fn detects_formats() {
+    assert_eq!(detect(SS), Some(ImportFormat::Ss));
+    assert_eq!(detect(SS_NETID), Some(ImportFormat::Ss));
+    assert_eq!(detect(NETSTAT), Some(ImportFormat::Netstat));
+    assert_eq!(detect(LSOF), Some(ImportFormat::Lsof));
+
+    // Without headers, by what parses.
+    let body = |text: &str| text.lines().skip(1).collect::<Vec<_>>().join("\n");
+    assert_eq!(detect(&body(SS)), Some(ImportFormat::Ss));
+    assert_eq!(detect(&body(LSOF)), Some(ImportFormat::Lsof));
+    assert_eq!(detect("hello\nworld"), None);
+}
+
+#[test]
+fn endpoints() {
+    assert_eq!(
+        endpoint("[fe80::1%eth0]:546"),
+        Some(AddressPort {
+            address: Some("fe80::1".into()),
+            port: Some(546),
+            hostname: None,
+        })
+    );
+    assert_eq!(
+        endpoint(":::22"),
+        Some(AddressPort {
+            address: None,
+            port: Some(22),
+            hostname: None,
+        })
+    );
+    assert_eq!(
+        endpoint("*:*"),
+        Some(AddressPort {
+            address: None,
+            port: None,
+            hostname: None,
+        })
+    );
+    assert_eq!(endpoint("localhost:ssh"), None);
+}
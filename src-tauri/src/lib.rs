@@ -1,42 +1,69 @@
-mod netstat;
-mod process_info;
+pub mod audit;
+pub mod batch_kill;
+pub mod changes;
+#[cfg(feature = "gui")]
+mod commands;
+pub mod dns;
+pub mod error;
+pub mod export;
+pub mod free_port;
+pub mod history;
+pub mod import;
+pub mod netstat;
+pub mod process_control;
+pub mod process_details;
+pub mod process_info;
+pub mod process_table;
+pub mod protection;
+pub mod query;
+#[cfg(feature = "gui")]
+mod recorder;
+#[cfg(feature = "gui")]
+mod sampler;
+pub mod scanner;
+pub mod snapshot;
+pub mod snapshot_diff;
+pub mod tracker;
+#[cfg(feature = "gui")]
+mod watcher;
 
-use process_info::ProcessInfo;
-use sysinfo::{Pid, ProcessesToUpdate, System};
-
-#[tauri::command]
-fn get_process_info_list() -> Result<Vec<ProcessInfo>, String> {
-    netstat::fetch_process_info_list()
-}
-
-#[tauri::command]
-fn get_process_path(_pid: u32) -> String {
-    // Stub — same as the current Electron implementation
-    String::new()
-}
-
-#[tauri::command]
-fn kill_process(pid: u32) -> Result<(), String> {
-    let mut sys = System::new();
-    sys.refresh_processes(ProcessesToUpdate::All, true);
-
-    let process = sys
-        .process(Pid::from_u32(pid))
-        .ok_or_else(|| format!("Process with PID {} not found", pid))?;
-
-    if process.kill() {
-        Ok(())
-    } else {
-        Err(format!("Failed to kill process with PID {}", pid))
-    }
-}
+#[cfg(feature = "gui")]
+use audit::AuditLog;
+#[cfg(feature = "gui")]
+use dns::ReverseDns;
+#[cfg(feature = "gui")]
+use process_table::ProcessTable;
+#[cfg(feature = "gui")]
+use protection::Protection;
+#[cfg(feature = "gui")]
+use recorder::Recorder;
+#[cfg(feature = "gui")]
+use sampler::Sampler;
+#[cfg(feature = "gui")]
+use scanner::Scanner;
+#[cfg(feature = "gui")]
+use tracker::ConnectionTracker;
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(ConnectionTracker::default())
+        .manage(Protection::default())
+        .manage(ProcessTable::default())
+        .manage(Scanner::default())
+        .manage(ReverseDns::default())
         .setup(|app| {
+            use tauri::Manager;
+            let log_dir = app.path().app_log_dir()?;
+            app.manage(AuditLog::new(log_dir.join("kill-audit.log")));
+            let data_dir = app.path().app_data_dir()?;
+            app.manage(Recorder::new(data_dir.join("history.sqlite3")));
+            app.manage(Sampler::new(app.handle().clone()));
+
             #[cfg(desktop)]
-            app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
+            app.handle()
+                .plugin(tauri_plugin_updater::Builder::new().build())?;
             // decorations: true in tauri.conf.json is required for macOS — it keeps the
             // native traffic light buttons (close/minimize/fullscreen). Combined with
             // titleBarStyle: "Overlay" and hiddenTitle: true, the title bar becomes
@@ -47,7 +74,6 @@ pub fn run() {
             // before the window becomes visible (visible: false in config).
             #[cfg(target_os = "windows")]
             {
-                use tauri::Manager;
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.set_decorations(false);
                 }
@@ -56,9 +82,35 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            get_process_info_list,
-            get_process_path,
-            kill_process
+            commands::get_process_info_list,
+            commands::get_filtered_process_info_list,
+            commands::cancel_scan,
+            commands::set_reverse_dns,
+            commands::is_reverse_dns_enabled,
+            commands::parse_filter,
+            commands::subscribe_connections,
+            commands::unsubscribe_connections,
+            commands::start_history_recording,
+            commands::stop_history_recording,
+            commands::is_history_recording,
+            commands::query_history,
+            commands::save_snapshot,
+            commands::load_snapshot,
+            commands::import_connections,
+            commands::export_connections,
+            commands::diff_snapshots,
+            commands::get_process_path,
+            commands::get_process_details,
+            commands::kill_process,
+            commands::kill_processes,
+            commands::suspend_process,
+            commands::resume_process,
+            commands::kill_process_tree,
+            commands::close_connection,
+            commands::free_port,
+            commands::get_process_table_stats,
+            commands::get_protection_policy,
+            commands::set_protection_policy
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,84 @@
+mod procfs;
+mod sock_diag;
+
+pub use sock_diag::destroy_tcp_socket;
+
+use std::io;
+
+use netstat2::TcpState;
+
+use super::source::{SocketEntry, SocketSource};
+use crate::error::NetstatCatError;
+
+/// Native Linux source. Sockets are dumped over `NETLINK_SOCK_DIAG`; if the
+/// kernel refuses the request (old kernel, missing `inet_diag` module,
+/// restrictive seccomp profile) we fall back to parsing `/proc/net/*`.
+/// Owning PIDs are resolved by walking `/proc/<pid>/fd`.
+pub struct LinuxSource;
+
+impl SocketSource for LinuxSource {
+    fn name(&self) -> &'static str {
+        "linux"
+    }
+
+    fn sockets(&self) -> Result<Vec<SocketEntry>, NetstatCatError> {
+        let mut sockets = with_fallback(sock_diag::dump_inet_sockets, procfs::read_inet_sockets)?;
+        sockets.extend(with_fallback(
+            sock_diag::dump_unix_sockets,
+            procfs::read_unix_sockets,
+        )?);
+
+        let pids_by_inode = procfs::pids_by_inode();
+        for socket in &mut sockets {
+            if let Some(pids) = socket.inode.and_then(|inode| pids_by_inode.get(&inode)) {
+                socket.pids = pids.clone();
+            }
+        }
+
+        Ok(sockets)
+    }
+}
+
+fn with_fallback(
+    primary: fn() -> io::Result<Vec<SocketEntry>>,
+    fallback: fn() -> io::Result<Vec<SocketEntry>>,
+) -> Result<Vec<SocketEntry>, NetstatCatError> {
+    primary().or_else(|diag_err| {
+        fallback().map_err(|proc_err| NetstatCatError::SocketEnumeration {
+            message: format!("Failed to get sockets: sock_diag: {diag_err}; procfs: {proc_err}"),
+            errno: proc_err.raw_os_error().or(diag_err.raw_os_error()),
+        })
+    })
+}
+
+/// Kernel-reported Unix socket names are raw `sun_path` bytes: empty for
+/// unnamed sockets and NUL-prefixed for the abstract namespace, which we
+/// render as `@name` like `ss` does.
+fn unix_path(name: &str) -> Option<String> {
+    let name = name.trim_end_matches('\0');
+    if name.is_empty() {
+        None
+    } else if let Some(abstract_name) = name.strip_prefix('\0') {
+        Some(format!("@{abstract_name}"))
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Maps the kernel's `TCP_*` state numbers (include/net/tcp_states.h).
+fn tcp_state_from_kernel(state: u8) -> TcpState {
+    match state {
+        1 => TcpState::Established,
+        2 => TcpState::SynSent,
+        3 => TcpState::SynReceived,
+        4 => TcpState::FinWait1,
+        5 => TcpState::FinWait2,
+        6 => TcpState::TimeWait,
+        7 => TcpState::Closed,
+        8 => TcpState::CloseWait,
+        9 => TcpState::LastAck,
+        10 => TcpState::Listen,
+        11 => TcpState::Closing,
+        _ => TcpState::Unknown,
+    }
+}
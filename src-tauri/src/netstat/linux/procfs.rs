@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use netstat2::TcpState;
+
+use super::{tcp_state_from_kernel, unix_path};
+use crate::netstat::source::{ProtocolEntry, SocketEntry, TcpEntry, UdpEntry, UnixEntry, UnixKind};
+use crate::process_info::TcpInfo;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Tcp,
+    Udp,
+}
+
+const TABLES: [(&str, Kind); 4] = [
+    ("/proc/net/tcp", Kind::Tcp),
+    ("/proc/net/tcp6", Kind::Tcp),
+    ("/proc/net/udp", Kind::Udp),
+    ("/proc/net/udp6", Kind::Udp),
+];
+
+/// Reads the `/proc/net/{tcp,tcp6,udp,udp6}` tables. A missing IPv6 table
+/// (IPv6 disabled) is not an error; an unreadable IPv4 table is.
+pub fn read_inet_sockets() -> io::Result<Vec<SocketEntry>> {
+    let mut sockets = Vec::new();
+    for (path, kind) in TABLES {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && path.ends_with('6') => continue,
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{path}: {e}"))),
+        };
+        sockets.extend(
+            contents
+                .lines()
+                .skip(1)
+                .filter_map(|line| parse_line(line, kind)),
+        );
+    }
+    Ok(sockets)
+}
+
+// "  0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 12345 ..."
+fn parse_line(line: &str, kind: Kind) -> Option<SocketEntry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_endpoint(fields[1])?;
+    let (remote_addr, remote_port) = parse_endpoint(fields[2])?;
+    let state = u8::from_str_radix(fields[3], 16).ok()?;
+    let (tx_queue, rx_queue) = fields[4].split_once(':')?;
+    let uid = fields[7].parse().ok()?;
+    let inode = fields[9].parse().ok()?;
+
+    let protocol = match kind {
+        Kind::Tcp => ProtocolEntry::Tcp(TcpEntry {
+            local_addr,
+            local_port,
+            remote_addr,
+            remote_port,
+            state: tcp_state_from_kernel(state),
+            tcp_info: Some(TcpInfo {
+                recv_q: u32::from_str_radix(rx_queue, 16).ok()?,
+                send_q: u32::from_str_radix(tx_queue, 16).ok()?,
+                ..TcpInfo::default()
+            }),
+        }),
+        Kind::Udp => ProtocolEntry::Udp(UdpEntry {
+            local_addr,
+            local_port,
+        }),
+    };
+
+    Some(SocketEntry {
+        protocol,
+        pids: Vec::new(),
+        inode: Some(inode),
+        uid: Some(uid),
+    })
+}
+
+/// Addresses are written as hex 32-bit words in host byte order.
+fn parse_endpoint(field: &str) -> Option<(IpAddr, u16)> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let addr = match addr.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(
+            u32::from_str_radix(addr, 16).ok()?.to_ne_bytes(),
+        )),
+        32 => {
+            let mut octets = [0u8; 16];
+            for (i, chunk) in octets.chunks_mut(4).enumerate() {
+                let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+
+    Some((addr, port))
+}
+
+/// `__SO_ACCEPTCON` in the Flags column marks a listening socket.
+const SO_ACCEPTCON: u32 = 1 << 16;
+/// `SS_CONNECTED` in the St column.
+const SS_CONNECTED: u8 = 3;
+
+/// Reads `/proc/net/unix`. Unlike `unix_diag` this table carries no peer
+/// information, so `peer_inode` is always `None` here.
+pub fn read_unix_sockets() -> io::Result<Vec<SocketEntry>> {
+    let contents = fs::read_to_string("/proc/net/unix")?;
+    Ok(contents
+        .lines()
+        .skip(1)
+        .filter_map(parse_unix_line)
+        .collect())
+}
+
+// "0000000000000000: 00000002 00000000 00010000 0001 01 12345 /run/dbus/system_bus_socket"
+fn parse_unix_line(line: &str) -> Option<SocketEntry> {
+    // The path is everything after the inode and may itself contain spaces,
+    // so split off the seven fixed columns one by one.
+    let mut fields = Vec::with_capacity(7);
+    let mut rest = line;
+    while fields.len() < 7 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    let flags = u32::from_str_radix(fields[3], 16).ok()?;
+    let kind = match u16::from_str_radix(fields[4], 16).ok()? {
+        2 => UnixKind::Dgram,
+        5 => UnixKind::SeqPacket,
+        _ => UnixKind::Stream,
+    };
+    let st = u8::from_str_radix(fields[5], 16).ok()?;
+    let inode = fields[6].parse().ok()?;
+    // Abstract names are already printed with a leading '@'.
+    let path = rest.strip_prefix(' ').and_then(unix_path);
+
+    let state = if flags & SO_ACCEPTCON != 0 {
+        TcpState::Listen
+    } else if st == SS_CONNECTED {
+        TcpState::Established
+    } else {
+        TcpState::Closed
+    };
+
+    Some(SocketEntry {
+        protocol: ProtocolEntry::Unix(UnixEntry {
+            kind,
+            path,
+            state,
+            peer_inode: None,
+        }),
+        pids: Vec::new(),
+        inode: Some(inode),
+        uid: None,
+    })
+}
+
+/// Builds a socket inode → owning PIDs map from `/proc/<pid>/fd/*` links.
+/// Processes we are not allowed to inspect are silently skipped.
+pub fn pids_by_inode() -> HashMap<u32, Vec<u32>> {
+    let mut map: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    let Ok(proc_dir) = fs::read_dir("/proc") else {
+        return map;
+    };
+
+    for pid in proc_dir.filter_map(|d| d.ok()?.file_name().to_str()?.parse::<u32>().ok()) {
+        let Ok(fds) = fs::read_dir(format!("/proc/{pid}/fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let Some(inode) = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let pids = map.entry(inode).or_default();
+            if !pids.contains(&pid) {
+                pids.push(pid);
+            }
+        }
+    }
+
+    map
+}
+
+// The fixtures are in the byte order of an x86 or ARM kernel.
+#[cfg(all(test, target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tcp4_line() {
+        let line = "   0: 0100007F:0277 00000000:0000 0A 00000002:00000001 00:00000000 00000000     0        0 12345 1 0000000000000000 100 0 0 10 0";
+        let entry = parse_line(line, Kind::Tcp).unwrap();
+        assert_eq!(entry.inode, Some(12345));
+        assert_eq!(entry.uid, Some(0));
+        let ProtocolEntry::Tcp(tcp) = entry.protocol else {
+            panic!("not tcp: {:?}", entry.protocol);
+        };
+        assert_eq!(tcp.local_addr, IpAddr::from([127, 0, 0, 1]));
+        assert_eq!(tcp.local_port, 631);
+        assert!(tcp.remote_addr.is_unspecified());
+        assert_eq!(tcp.remote_port, 0);
+        assert!(matches!(tcp.state, TcpState::Listen));
+        let info = tcp.tcp_info.unwrap();
+        assert_eq!((info.recv_q, info.send_q), (1, 2));
+    }
+
+    #[test]
+    fn udp6_line() {
+        let line = "  12: 000080FE000000000000000001000000:0222 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 23456 2 0000000000000000 0";
+        let entry = parse_line(line, Kind::Udp).unwrap();
+        assert_eq!(entry.uid, Some(1000));
+        let ProtocolEntry::Udp(udp) = entry.protocol else {
+            panic!("not udp: {:?}", entry.protocol);
+        };
+        assert_eq!(udp.local_addr, "fe80::1".parse::<IpAddr>().unwrap());
+        assert_eq!(udp.local_port, 546);
+    }
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(
+            parse_endpoint("00000000000000000000000001000000:0016"),
+            Some(("::1".parse().unwrap(), 22))
+        );
+        assert_eq!(
+            parse_endpoint("0101A8C0:D431"),
+            Some((IpAddr::from([192, 168, 1, 1]), 54321))
+        );
+        assert_eq!(parse_endpoint("0100007F"), None);
+        assert_eq!(parse_endpoint("0100007F:XYZ"), None);
+        assert_eq!(parse_endpoint("00007F:0016"), None);
+    }
+
+    #[test]
+    fn short_lines_are_skipped() {
+        assert!(parse_line("  sl  local_address rem_address   st", Kind::Tcp).is_none());
+        assert!(parse_unix_line("Num       RefCount Protocol Flags").is_none());
+    }
+
+    fn unix(line: &str) -> (UnixEntry, Option<u32>) {
+        let entry = parse_unix_line(line).unwrap();
+        let ProtocolEntry::Unix(unix) = entry.protocol else {
+            panic!("not unix: {:?}", entry.protocol);
+        };
+        (unix, entry.inode)
+    }
+
+    #[test]
+    fn unix_listening() {
+        let (entry, inode) = unix(
+            "0000000000000000: 00000002 00000000 00010000 0001 01 12345 /run/dbus/system_bus_socket",
+        );
+        assert_eq!(inode, Some(12345));
+        assert_eq!(entry.kind, UnixKind::Stream);
+        assert_eq!(entry.path.as_deref(), Some("/run/dbus/system_bus_socket"));
+        assert!(matches!(entry.state, TcpState::Listen));
+        assert_eq!(entry.peer_inode, None);
+    }
+
+    #[test]
+    fn unix_abstract_connected() {
+        let (entry, _) =
+            unix("0000000000000000: 00000003 00000000 00000000 0005 03 23456 @/tmp/.X11-unix/X0");
+        assert_eq!(entry.kind, UnixKind::SeqPacket);
+        assert_eq!(entry.path.as_deref(), Some("@/tmp/.X11-unix/X0"));
+        assert!(matches!(entry.state, TcpState::Established));
+    }
+
+    #[test]
+    fn unix_path_with_spaces() {
+        let (entry, _) = unix(
+            "0000000000000000: 00000002 00000000 00010000 0001 01 45678 /tmp/my app/ctl  sock",
+        );
+        assert_eq!(entry.path.as_deref(), Some("/tmp/my app/ctl  sock"));
+    }
+
+    #[test]
+    fn unix_unnamed() {
+        let (entry, inode) = unix("0000000000000000: 00000002 00000000 00000000 0002 01 34567");
+        assert_eq!(inode, Some(34567));
+        assert_eq!(entry.kind, UnixKind::Dgram);
+        assert_eq!(entry.path, None);
+        assert!(matches!(entry.state, TcpState::Closed));
+    }
+}
@@ -0,0 +1,320 @@
+use std::io;
+use std::net::SocketAddr;
+
+use netlink_packet_core::{
+    NetlinkHeader, NetlinkMessage, NetlinkPayload, NLM_F_ACK, NLM_F_DUMP, NLM_F_REQUEST,
+};
+use netlink_packet_sock_diag::{
+    constants::{
+        AF_INET, AF_INET6, IPPROTO_TCP, IPPROTO_UDP, SOCK_DESTROY, SOCK_DGRAM, SOCK_SEQPACKET,
+    },
+    inet::{nlas::Nla, ExtensionFlags, InetRequest, InetResponse, SocketId, StateFlags},
+    unix::{self, nlas::Nla as UnixNla, ShowFlags, UnixRequest, UnixResponse},
+    SockDiagMessage,
+};
+use netlink_packet_utils::nla::Nla as _;
+use netlink_sys::{protocols::NETLINK_SOCK_DIAG, Socket, SocketAddr as NetlinkAddr};
+
+use super::{tcp_state_from_kernel, unix_path};
+use crate::netstat::source::{ProtocolEntry, SocketEntry, TcpEntry, UdpEntry, UnixEntry, UnixKind};
+use crate::process_info::TcpInfo;
+
+/// Size of `struct nlmsghdr`; the request payload starts right after it.
+const NETLINK_HEADER_LEN: usize = 16;
+
+/// `UDIAG_SHOW_UID` and the `UNIX_DIAG_UID` attribute it adds (Linux 4.19+),
+/// neither of which the `ShowFlags`/`Nla` types know about.
+const UDIAG_SHOW_UID: u32 = 0x40;
+const UNIX_DIAG_UID: u16 = 7;
+
+/// Dumps every TCP and UDP socket, IPv4 and IPv6, through `inet_diag`.
+pub fn dump_inet_sockets() -> io::Result<Vec<SocketEntry>> {
+    let mut sockets = Vec::new();
+    for family in [AF_INET, AF_INET6] {
+        for protocol in [IPPROTO_TCP, IPPROTO_UDP] {
+            let socket_id = if family == AF_INET6 {
+                SocketId::new_v6()
+            } else {
+                SocketId::new_v4()
+            };
+            let request = serialize(SockDiagMessage::InetRequest(InetRequest {
+                family,
+                protocol,
+                extensions: if protocol == IPPROTO_TCP {
+                    ExtensionFlags::INFO | ExtensionFlags::CONG
+                } else {
+                    ExtensionFlags::empty()
+                },
+                states: StateFlags::all(),
+                socket_id,
+            }));
+
+            for message in dump(&request)? {
+                if let SockDiagMessage::InetResponse(response) = message {
+                    sockets.push(inet_entry(&response, protocol));
+                }
+            }
+        }
+    }
+    Ok(sockets)
+}
+
+/// Dumps every Unix domain socket through `unix_diag`, with bound names and
+/// peer inodes.
+pub fn dump_unix_sockets() -> io::Result<Vec<SocketEntry>> {
+    let mut request = serialize(SockDiagMessage::UnixRequest(UnixRequest {
+        state_flags: unix::StateFlags::all(),
+        inode: 0,
+        show_flags: ShowFlags::NAME | ShowFlags::PEER,
+        cookie: [0xff; 8],
+    }));
+    // `unix::StateFlags` only knows LISTEN and ESTABLISHED, which would hide
+    // unconnected datagram sockets (TCP_CLOSE). Ask for every state.
+    request[NETLINK_HEADER_LEN + 4..NETLINK_HEADER_LEN + 8]
+        .copy_from_slice(&u32::MAX.to_ne_bytes());
+    // Same for the owner's uid; older kernels ignore the unknown flag.
+    let show_flags = (ShowFlags::NAME | ShowFlags::PEER).bits() | UDIAG_SHOW_UID;
+    request[NETLINK_HEADER_LEN + 12..NETLINK_HEADER_LEN + 16]
+        .copy_from_slice(&show_flags.to_ne_bytes());
+
+    Ok(dump(&request)?
+        .into_iter()
+        .filter_map(|message| match message {
+            SockDiagMessage::UnixResponse(response) => Some(unix_entry(&response)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Aborts one TCP socket with `SOCK_DESTROY`, like `ss -K`. The socket is
+/// looked up by its 4-tuple first; when `inode` is given it must still be
+/// the same socket, so a reused tuple is never hit.
+///
+/// Fails with `EOPNOTSUPP` on kernels built without
+/// `CONFIG_INET_DIAG_DESTROY` and `EPERM` without `CAP_NET_ADMIN`.
+pub fn destroy_tcp_socket(
+    local: SocketAddr,
+    remote: SocketAddr,
+    inode: Option<u32>,
+) -> io::Result<()> {
+    let not_found = || io::Error::from_raw_os_error(libc::ENOENT);
+    let family = if local.is_ipv6() { AF_INET6 } else { AF_INET };
+    let mut socket_id = if local.is_ipv6() {
+        SocketId::new_v6()
+    } else {
+        SocketId::new_v4()
+    };
+    socket_id.source_address = local.ip();
+    socket_id.source_port = local.port();
+    socket_id.destination_address = remote.ip();
+    socket_id.destination_port = remote.port();
+    // All ones means "any cookie" (INET_DIAG_NOCOOKIE).
+    socket_id.cookie = [0xff; 8];
+
+    let inet_request = |socket_id: SocketId, flags: u16| {
+        serialize_with_flags(
+            SockDiagMessage::InetRequest(InetRequest {
+                family,
+                protocol: IPPROTO_TCP,
+                extensions: ExtensionFlags::empty(),
+                states: StateFlags::all(),
+                socket_id,
+            }),
+            flags,
+        )
+    };
+
+    // An exact lookup returns the socket's cookie, which pins the destroy
+    // request to this very socket.
+    let found = match request(&inet_request(socket_id, NLM_F_REQUEST))? {
+        Some(SockDiagMessage::InetResponse(response)) => response,
+        _ => return Err(not_found()),
+    };
+    if inode.is_some_and(|inode| inode != found.header.inode) {
+        return Err(not_found());
+    }
+
+    let mut destroy = inet_request(found.header.socket_id.clone(), NLM_F_REQUEST | NLM_F_ACK);
+    destroy[4..6].copy_from_slice(&SOCK_DESTROY.to_ne_bytes());
+    request(&destroy).map(|_| ())
+}
+
+fn serialize(message: SockDiagMessage) -> Vec<u8> {
+    serialize_with_flags(message, NLM_F_REQUEST | NLM_F_DUMP)
+}
+
+fn serialize_with_flags(message: SockDiagMessage, flags: u16) -> Vec<u8> {
+    let mut header = NetlinkHeader::default();
+    header.flags = flags;
+    let mut packet = NetlinkMessage::new(header, message.into());
+    packet.finalize();
+
+    let mut buf = vec![0; packet.buffer_len()];
+    packet.serialize(&mut buf[..]);
+    buf
+}
+
+fn connect() -> io::Result<Socket> {
+    let mut socket = Socket::new(NETLINK_SOCK_DIAG)?;
+    socket.bind_auto()?;
+    socket.connect(&NetlinkAddr::new(0, 0))?;
+    Ok(socket)
+}
+
+/// Sends a non-dump request and returns the first reply, or `None` for a
+/// bare acknowledgement.
+fn request(request: &[u8]) -> io::Result<Option<SockDiagMessage>> {
+    let socket = connect()?;
+    socket.send(request, 0)?;
+
+    loop {
+        let (data, _) = socket.recv_from_full()?;
+        let message = NetlinkMessage::<SockDiagMessage>::deserialize(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        match message.payload {
+            NetlinkPayload::InnerMessage(inner) => return Ok(Some(inner)),
+            NetlinkPayload::Error(err) if err.code.is_none() => return Ok(None),
+            NetlinkPayload::Error(err) => return Err(err.to_io()),
+            _ => {}
+        }
+    }
+}
+
+fn dump(request: &[u8]) -> io::Result<Vec<SockDiagMessage>> {
+    let socket = connect()?;
+    socket.send(request, 0)?;
+
+    let mut responses = Vec::new();
+    loop {
+        let (data, _) = socket.recv_from_full()?;
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let message = NetlinkMessage::<SockDiagMessage>::deserialize(&data[offset..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            let length = message.header.length as usize;
+            if length == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "zero-length netlink message",
+                ));
+            }
+            offset += length;
+
+            match message.payload {
+                NetlinkPayload::InnerMessage(inner) => responses.push(inner),
+                NetlinkPayload::Done(_) => return Ok(responses),
+                NetlinkPayload::Error(err) => return Err(err.to_io()),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn inet_entry(response: &InetResponse, protocol: u8) -> SocketEntry {
+    let header = &response.header;
+    let id = &header.socket_id;
+
+    let protocol = if protocol == IPPROTO_TCP {
+        ProtocolEntry::Tcp(TcpEntry {
+            local_addr: id.source_address,
+            local_port: id.source_port,
+            remote_addr: id.destination_address,
+            remote_port: id.destination_port,
+            state: tcp_state_from_kernel(header.state),
+            tcp_info: Some(tcp_info(response)),
+        })
+    } else {
+        ProtocolEntry::Udp(UdpEntry {
+            local_addr: id.source_address,
+            local_port: id.source_port,
+        })
+    };
+
+    SocketEntry {
+        protocol,
+        pids: Vec::new(),
+        inode: Some(header.inode),
+        uid: Some(header.uid),
+    }
+}
+
+/// Sentinel `snd_ssthresh` value while a connection is still in slow start.
+const TCP_INFINITE_SSTHRESH: u32 = 0x7fff_ffff;
+
+fn tcp_info(response: &InetResponse) -> TcpInfo {
+    let mut info = TcpInfo {
+        recv_q: response.header.recv_queue,
+        send_q: response.header.send_queue,
+        ..TcpInfo::default()
+    };
+
+    for nla in &response.nlas {
+        match nla {
+            // `struct tcp_info` has grown over kernel releases, so read it
+            // field by field and leave whatever this kernel omits as `None`.
+            Nla::TcpInfo(raw) => {
+                info.retransmits = raw.get(2).copied();
+                info.lost = read_u32(raw, 32);
+                info.rtt_us = read_u32(raw, 68);
+                info.rttvar_us = read_u32(raw, 72);
+                info.ssthresh = read_u32(raw, 76).filter(|&v| v < TCP_INFINITE_SSTHRESH);
+                info.cwnd = read_u32(raw, 80);
+                info.total_retrans = read_u32(raw, 100);
+                // ~0 means pacing is not limited.
+                info.pacing_rate = read_u64(raw, 104).filter(|&v| v != u64::MAX);
+                info.bytes_acked = read_u64(raw, 120);
+                info.bytes_received = read_u64(raw, 128);
+                info.delivery_rate = read_u64(raw, 160);
+            }
+            Nla::Congestion(name) => info.congestion = Some(name.clone()),
+            _ => {}
+        }
+    }
+
+    info
+}
+
+fn read_u32(raw: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        raw.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(raw: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        raw.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn unix_entry(response: &UnixResponse) -> SocketEntry {
+    let header = &response.header;
+    let kind = match header.kind {
+        SOCK_DGRAM => UnixKind::Dgram,
+        SOCK_SEQPACKET => UnixKind::SeqPacket,
+        _ => UnixKind::Stream,
+    };
+
+    SocketEntry {
+        protocol: ProtocolEntry::Unix(UnixEntry {
+            kind,
+            path: response.name().and_then(|name| unix_path(name)),
+            state: tcp_state_from_kernel(header.state),
+            peer_inode: response.peer().filter(|&inode| inode != 0),
+        }),
+        pids: Vec::new(),
+        inode: Some(header.inode),
+        uid: unix_uid(response),
+    }
+}
+
+fn unix_uid(response: &UnixResponse) -> Option<u32> {
+    response.nlas.iter().find_map(|nla| match nla {
+        UnixNla::Other(other) if other.kind() == UNIX_DIAG_UID && other.value_len() == 4 => {
+            let mut value = [0; 4];
+            other.emit_value(&mut value);
+            Some(u32::from_ne_bytes(value))
+        }
+        _ => None,
+    })
+}
@@ -0,0 +1,335 @@
+#[cfg(target_os = "linux")]
+mod linux;
+mod netstat2_source;
+pub mod source;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use netstat2::TcpState;
+
+use crate::error::NetstatCatError;
+use crate::process_info::{connection_id, AddressPort, PeerInfo, ProcessInfo, ProcessState};
+use crate::process_table::ProcessTable;
+
+#[cfg(target_os = "linux")]
+pub use linux::LinuxSource;
+pub use netstat2_source::Netstat2Source;
+pub use source::SocketSource;
+
+use source::{ProtocolEntry, UnixKind};
+
+fn tcp_state_to_string(state: &TcpState) -> &'static str {
+    match state {
+        TcpState::Closed => "CLOSED",
+        TcpState::Listen => "LISTEN",
+        TcpState::SynSent => "SYN_SENT",
+        TcpState::SynReceived => "SYN_RECEIVED",
+        TcpState::Established => "ESTABLISHED",
+        TcpState::FinWait1 => "FIN_WAIT_1",
+        TcpState::FinWait2 => "FIN_WAIT_2",
+        TcpState::CloseWait => "CLOSE_WAIT",
+        TcpState::Closing => "CLOSING",
+        TcpState::LastAck => "LAST_ACK",
+        TcpState::TimeWait => "TIME_WAIT",
+        TcpState::DeleteTcb => "DELETE_TCB",
+        TcpState::Unknown => "UNKNOWN",
+    }
+}
+
+fn is_wildcard(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => v4.is_unspecified(),
+        IpAddr::V6(v6) => v6.is_unspecified(),
+    }
+}
+
+fn normalize_address(addr: &IpAddr) -> Option<String> {
+    if is_wildcard(addr) {
+        None
+    } else {
+        Some(addr.to_string())
+    }
+}
+
+fn is_ipv6(addr: &IpAddr) -> bool {
+    matches!(addr, IpAddr::V6(_))
+}
+
+/// Environment variable that forces a specific [`SocketSource`] by name.
+pub const SOURCE_ENV_VAR: &str = "NETSTAT_CAT_SOCKET_SOURCE";
+
+fn available_sources() -> Vec<Box<dyn SocketSource>> {
+    vec![
+        #[cfg(target_os = "linux")]
+        Box::new(LinuxSource),
+        Box::new(Netstat2Source),
+    ]
+}
+
+/// Names accepted by [`source_by_name`], best first.
+pub fn source_names() -> Vec<&'static str> {
+    available_sources().iter().map(|s| s.name()).collect()
+}
+
+pub fn source_by_name(name: &str) -> Option<Box<dyn SocketSource>> {
+    available_sources().into_iter().find(|s| s.name() == name)
+}
+
+/// The best socket source available on this platform, unless overridden
+/// through [`SOURCE_ENV_VAR`].
+pub fn default_source() -> Box<dyn SocketSource> {
+    std::env::var(SOURCE_ENV_VAR)
+        .ok()
+        .and_then(|wanted| source_by_name(&wanted))
+        .unwrap_or_else(|| available_sources().swap_remove(0))
+}
+
+pub fn fetch_process_info_list(table: &ProcessTable) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    fetch_process_info_list_from(default_source().as_ref(), table)
+}
+
+pub fn fetch_process_info_list_from(
+    source: &dyn SocketSource,
+    table: &ProcessTable,
+) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    fetch_process_info_list_with(source, table, &|| false)
+}
+
+/// [`fetch_process_info_list_from`] that gives up with
+/// [`NetstatCatError::Cancelled`] between its expensive steps once
+/// `is_cancelled` returns true.
+pub fn fetch_process_info_list_with(
+    source: &dyn SocketSource,
+    table: &ProcessTable,
+    is_cancelled: &dyn Fn() -> bool,
+) -> Result<Vec<ProcessInfo>, NetstatCatError> {
+    let sockets = source.sockets()?;
+    if is_cancelled() {
+        return Err(NetstatCatError::Cancelled);
+    }
+
+    // Resolve only the PIDs that hold sockets.
+    let pids: Vec<u32> = sockets
+        .iter()
+        .flat_map(|s| s.pids.iter().copied())
+        .collect();
+    let processes = table.resolve(&pids);
+    if is_cancelled() {
+        return Err(NetstatCatError::Cancelled);
+    }
+    let mut pid_name_map: HashMap<u32, String> = HashMap::new();
+    let mut pid_state_map: HashMap<u32, ProcessState> = HashMap::new();
+    for (pid, process) in processes {
+        pid_state_map.insert(pid, process.state);
+        pid_name_map.insert(pid, process.name);
+    }
+
+    // Unix peers are other rows of the same dump; index them by inode so the
+    // far end of a connection can be attributed to its process.
+    let mut unix_by_inode: HashMap<u32, (Option<String>, Vec<u32>)> = HashMap::new();
+    for socket in &sockets {
+        if let (ProtocolEntry::Unix(unix), Some(inode)) = (&socket.protocol, socket.inode) {
+            unix_by_inode.insert(inode, (unix.path.clone(), socket.pids.clone()));
+        }
+    }
+
+    let mut results = Vec::new();
+
+    for socket in sockets {
+        // A socket inherited across fork() (pre-forked servers) has several
+        // owners. Keep them all; the first one stays the "primary" PID.
+        let mut pids: Vec<u32> = Vec::with_capacity(socket.pids.len());
+        for pid in &socket.pids {
+            if !pids.contains(pid) {
+                pids.push(*pid);
+            }
+        }
+        let process_names: Vec<String> = pids
+            .iter()
+            .map(|pid| pid_name_map.get(pid).cloned().unwrap_or_default())
+            .collect();
+        let pid = pids.first().copied().unwrap_or(0);
+        let process_name = process_names.first().cloned().unwrap_or_default();
+        let process_state = pid_state_map.get(&pid).copied();
+
+        match socket.protocol {
+            ProtocolEntry::Tcp(tcp) => {
+                let v6 = is_ipv6(&tcp.local_addr);
+                let protocol = if v6 { "tcp6" } else { "tcp" }.to_string();
+
+                results.push(ProcessInfo {
+                    id: String::new(),
+                    protocol,
+                    local: AddressPort {
+                        address: normalize_address(&tcp.local_addr),
+                        port: Some(tcp.local_port),
+                        hostname: None,
+                    },
+                    remote: AddressPort {
+                        address: normalize_address(&tcp.remote_addr),
+                        port: Some(tcp.remote_port),
+                        hostname: None,
+                    },
+                    state: tcp_state_to_string(&tcp.state).to_string(),
+                    pid,
+                    process_name,
+                    pids,
+                    process_names,
+                    process_state,
+                    uid: socket.uid,
+                    inode: socket.inode,
+                    peer: None,
+                    tcp_info: tcp.tcp_info,
+                    first_seen: None,
+                    age: None,
+                    state_changed_at: None,
+                });
+            }
+            ProtocolEntry::Udp(udp) => {
+                let v6 = is_ipv6(&udp.local_addr);
+                let protocol = if v6 { "udp6" } else { "udp" }.to_string();
+
+                results.push(ProcessInfo {
+                    id: String::new(),
+                    protocol,
+                    local: AddressPort {
+                        address: normalize_address(&udp.local_addr),
+                        port: Some(udp.local_port),
+                        hostname: None,
+                    },
+                    remote: AddressPort {
+                        address: None,
+                        port: None,
+                        hostname: None,
+                    },
+                    state: String::new(),
+                    pid,
+                    process_name,
+                    pids,
+                    process_names,
+                    process_state,
+                    uid: socket.uid,
+                    inode: socket.inode,
+                    peer: None,
+                    tcp_info: None,
+                    first_seen: None,
+                    age: None,
+                    state_changed_at: None,
+                });
+            }
+            ProtocolEntry::Unix(unix) => {
+                let protocol = match unix.kind {
+                    UnixKind::Stream => "unix",
+                    UnixKind::Dgram => "unix-dgram",
+                    UnixKind::SeqPacket => "unix-seqpacket",
+                }
+                .to_string();
+
+                let peer_entry = unix.peer_inode.map(|inode| {
+                    let (path, pids) = unix_by_inode.get(&inode).cloned().unwrap_or_default();
+                    (inode, path, pids)
+                });
+                let remote_address = peer_entry.as_ref().and_then(|(_, path, _)| path.clone());
+                let peer = peer_entry.map(|(inode, _, pids)| PeerInfo {
+                    inode,
+                    process_names: pids
+                        .iter()
+                        .map(|pid| pid_name_map.get(pid).cloned().unwrap_or_default())
+                        .collect(),
+                    pids,
+                });
+
+                results.push(ProcessInfo {
+                    id: String::new(),
+                    protocol,
+                    local: AddressPort {
+                        address: unix.path,
+                        port: None,
+                        hostname: None,
+                    },
+                    remote: AddressPort {
+                        address: remote_address,
+                        port: None,
+                        hostname: None,
+                    },
+                    state: tcp_state_to_string(&unix.state).to_string(),
+                    pid,
+                    process_name,
+                    pids,
+                    process_names,
+                    process_state,
+                    uid: socket.uid,
+                    inode: socket.inode,
+                    peer,
+                    tcp_info: None,
+                    first_seen: None,
+                    age: None,
+                    state_changed_at: None,
+                });
+            }
+        }
+    }
+
+    for info in &mut results {
+        info.id = connection_id(info);
+    }
+
+    Ok(results)
+}
+
+/// Forcibly closes a TCP connection without touching its process; both
+/// ends see a reset.
+pub fn close_connection(info: &ProcessInfo) -> Result<(), NetstatCatError> {
+    if !info.protocol.starts_with("tcp") {
+        return Err(NetstatCatError::InvalidArgument(format!(
+            "Only TCP connections can be closed, not {}",
+            info.protocol
+        )));
+    }
+    close_tcp_connection(info)
+}
+
+#[cfg(target_os = "linux")]
+fn close_tcp_connection(info: &ProcessInfo) -> Result<(), NetstatCatError> {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    let endpoint = |ap: &AddressPort| -> Result<SocketAddr, NetstatCatError> {
+        // Wildcards were normalized away; restore the family's "any".
+        let ip = match &ap.address {
+            Some(address) => address.parse().map_err(|_| {
+                NetstatCatError::InvalidArgument(format!("Invalid address '{address}'"))
+            })?,
+            None if info.protocol == "tcp6" => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            None => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        };
+        Ok(SocketAddr::new(ip, ap.port.unwrap_or(0)))
+    };
+
+    linux::destroy_tcp_socket(endpoint(&info.local)?, endpoint(&info.remote)?, info.inode).map_err(
+        |err| match err.raw_os_error() {
+            Some(libc::ENOENT) => NetstatCatError::ConnectionNotFound {
+                id: info.id.clone(),
+            },
+            Some(libc::EOPNOTSUPP) => NetstatCatError::Unsupported(
+                "This kernel cannot close sockets (built without CONFIG_INET_DIAG_DESTROY)"
+                    .to_string(),
+            ),
+            Some(errno @ (libc::EPERM | libc::EACCES)) => NetstatCatError::PermissionDenied {
+                pid: None,
+                errno: Some(errno),
+            },
+            errno => NetstatCatError::Io {
+                message: format!("Failed to close connection: {err}"),
+                errno,
+            },
+        },
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn close_tcp_connection(_info: &ProcessInfo) -> Result<(), NetstatCatError> {
+    Err(NetstatCatError::Unsupported(
+        "Closing individual connections is only supported on Linux".to_string(),
+    ))
+}
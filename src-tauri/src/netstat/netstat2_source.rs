@@ -0,0 +1,63 @@
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo};
+
+use super::source::{ProtocolEntry, SocketEntry, SocketSource, TcpEntry, UdpEntry};
+use crate::error::NetstatCatError;
+
+/// Cross-platform source backed by the `netstat2` crate.
+pub struct Netstat2Source;
+
+impl SocketSource for Netstat2Source {
+    fn name(&self) -> &'static str {
+        "netstat2"
+    }
+
+    fn sockets(&self) -> Result<Vec<SocketEntry>, NetstatCatError> {
+        let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+        let proto_flags = ProtocolFlags::TCP | ProtocolFlags::UDP;
+
+        let sockets = get_sockets_info(af_flags, proto_flags).map_err(|e| {
+            let errno = match &e {
+                netstat2::error::Error::OsError(io)
+                | netstat2::error::Error::FailedToListProcesses(io)
+                | netstat2::error::Error::FailedToQueryFileDescriptors(io) => io.raw_os_error(),
+                _ => None,
+            };
+            NetstatCatError::SocketEnumeration {
+                message: format!("Failed to get sockets: {e}"),
+                errno,
+            }
+        })?;
+
+        Ok(sockets
+            .into_iter()
+            .map(|socket| {
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                let (inode, uid) = (Some(socket.inode), Some(socket.uid));
+                #[cfg(not(any(target_os = "linux", target_os = "android")))]
+                let (inode, uid) = (None, None);
+
+                let protocol = match socket.protocol_socket_info {
+                    ProtocolSocketInfo::Tcp(tcp) => ProtocolEntry::Tcp(TcpEntry {
+                        local_addr: tcp.local_addr,
+                        local_port: tcp.local_port,
+                        remote_addr: tcp.remote_addr,
+                        remote_port: tcp.remote_port,
+                        state: tcp.state,
+                        tcp_info: None,
+                    }),
+                    ProtocolSocketInfo::Udp(udp) => ProtocolEntry::Udp(UdpEntry {
+                        local_addr: udp.local_addr,
+                        local_port: udp.local_port,
+                    }),
+                };
+
+                SocketEntry {
+                    protocol,
+                    pids: socket.associated_pids,
+                    inode,
+                    uid,
+                }
+            })
+            .collect())
+    }
+}
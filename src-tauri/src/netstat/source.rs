@@ -0,0 +1,69 @@
+use std::net::IpAddr;
+
+use netstat2::TcpState;
+
+use crate::error::NetstatCatError;
+use crate::process_info::TcpInfo;
+
+/// A socket as reported by a [`SocketSource`], before owning processes are
+/// resolved to names.
+#[derive(Debug, Clone)]
+pub struct SocketEntry {
+    pub protocol: ProtocolEntry,
+    pub pids: Vec<u32>,
+    /// Socket inode, where the platform exposes one (Linux).
+    pub inode: Option<u32>,
+    /// Owner UID, where the platform exposes one (Linux).
+    pub uid: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProtocolEntry {
+    Tcp(TcpEntry),
+    Udp(UdpEntry),
+    Unix(UnixEntry),
+}
+
+#[derive(Debug, Clone)]
+pub struct TcpEntry {
+    pub local_addr: IpAddr,
+    pub local_port: u16,
+    pub remote_addr: IpAddr,
+    pub remote_port: u16,
+    pub state: TcpState,
+    pub tcp_info: Option<TcpInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UdpEntry {
+    pub local_addr: IpAddr,
+    pub local_port: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnixKind {
+    Stream,
+    Dgram,
+    SeqPacket,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnixEntry {
+    pub kind: UnixKind,
+    /// Bound path, or `@name` for the abstract namespace. `None` when unnamed.
+    pub path: Option<String>,
+    pub state: TcpState,
+    /// Inode of the socket on the other end, for connected sockets.
+    pub peer_inode: Option<u32>,
+}
+
+/// A backend able to enumerate the sockets currently open on this host.
+///
+/// Implementations only report sockets and their owning PIDs; turning PIDs
+/// into process names is done once in [`super::fetch_process_info_list`].
+pub trait SocketSource: Send + Sync {
+    /// Short identifier used in error messages.
+    fn name(&self) -> &'static str;
+
+    fn sockets(&self) -> Result<Vec<SocketEntry>, NetstatCatError>;
+}
@@ -0,0 +1,487 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NetstatCatError;
+use crate::process_table::ProcessTable;
+use crate::protection::Guard;
+
+pub const DEFAULT_GRACE_MS: u64 = 3000;
+/// How long a plain (non-escalating) kill waits to see the process go.
+const SETTLE: Duration = Duration::from_millis(300);
+const POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Term,
+    Int,
+    Hup,
+    Kill,
+    /// Unix only.
+    Stop,
+    /// Unix only.
+    Cont,
+    /// Any other signal by number (Unix only).
+    Number(i32),
+}
+
+impl Signal {
+    /// Whether the process is expected to go away after this signal.
+    fn terminates(self) -> bool {
+        !matches!(self, Signal::Stop | Signal::Cont)
+    }
+
+    /// Numbers of the named signals map back to their names.
+    fn from_number(n: i32) -> Signal {
+        #[cfg(unix)]
+        {
+            let named = [
+                Signal::Term,
+                Signal::Int,
+                Signal::Hup,
+                Signal::Kill,
+                Signal::Stop,
+                Signal::Cont,
+            ];
+            if let Some(signal) = named.into_iter().find(|s| s.number() == n) {
+                return signal;
+            }
+        }
+        Signal::Number(n)
+    }
+
+    #[cfg(unix)]
+    fn number(self) -> i32 {
+        match self {
+            Signal::Term => libc::SIGTERM,
+            Signal::Int => libc::SIGINT,
+            Signal::Hup => libc::SIGHUP,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Cont => libc::SIGCONT,
+            Signal::Number(n) => n,
+        }
+    }
+}
+
+/// Accepts `TERM`, `SIGTERM`, `term` or a plain number like `15`.
+impl FromStr for Signal {
+    type Err = NetstatCatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        Ok(match name {
+            "TERM" => Signal::Term,
+            "INT" => Signal::Int,
+            "HUP" => Signal::Hup,
+            "KILL" => Signal::Kill,
+            "STOP" => Signal::Stop,
+            "CONT" => Signal::Cont,
+            _ => match name.parse::<i32>() {
+                Ok(n) if (1..=64).contains(&n) => Signal::from_number(n),
+                _ => {
+                    return Err(NetstatCatError::InvalidArgument(format!(
+                        "Unknown signal '{s}'"
+                    )))
+                }
+            },
+        })
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Term => f.write_str("SIGTERM"),
+            Signal::Int => f.write_str("SIGINT"),
+            Signal::Hup => f.write_str("SIGHUP"),
+            Signal::Kill => f.write_str("SIGKILL"),
+            Signal::Stop => f.write_str("SIGSTOP"),
+            Signal::Cont => f.write_str("SIGCONT"),
+            Signal::Number(n) => write!(f, "signal {n}"),
+        }
+    }
+}
+
+impl Serialize for Signal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KillOptions {
+    pub signal: Signal,
+    /// When set, a process still alive after this long gets `SIGKILL`.
+    pub escalate_after: Option<Duration>,
+}
+
+impl Default for KillOptions {
+    fn default() -> Self {
+        KillOptions {
+            signal: Signal::Term,
+            escalate_after: Some(Duration::from_millis(DEFAULT_GRACE_MS)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillReport {
+    pub pid: u32,
+    /// Signals delivered, in order.
+    pub sent: Vec<Signal>,
+    pub exited: bool,
+    /// The signal after which the process exited, if it did.
+    pub ended_by: Option<Signal>,
+}
+
+impl KillReport {
+    fn new(pid: u32) -> Self {
+        KillReport {
+            pid,
+            sent: Vec::new(),
+            exited: false,
+            ended_by: None,
+        }
+    }
+
+    fn mark_exited(&mut self) {
+        self.exited = true;
+        self.ended_by = self.sent.last().copied();
+    }
+}
+
+/// Sends `options.signal` and, when escalating, follows up with `SIGKILL`
+/// if the process outlives the grace period.
+/// With a `guard`, protected processes are refused before anything is sent.
+pub fn kill(
+    pid: u32,
+    options: KillOptions,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Result<KillReport, NetstatCatError> {
+    if let Some(guard) = guard {
+        guard.check(pid)?;
+    }
+    let mut report = KillReport::new(pid);
+    send_first(pid, options, table, &mut report)?;
+
+    let mut reports = [&mut report];
+    if let Some((_, err)) = follow_up(&mut reports, options, table).into_iter().next() {
+        return Err(err);
+    }
+    Ok(report)
+}
+
+/// Sends `options.signal` and records it. When the process refuses it
+/// outright (a windowless process on Windows) and escalation is on, sends
+/// `SIGKILL` right away instead of waiting out a grace period that cannot
+/// help.
+fn send_first(
+    pid: u32,
+    options: KillOptions,
+    table: &ProcessTable,
+    report: &mut KillReport,
+) -> Result<(), NetstatCatError> {
+    let signal = match send(pid, options.signal, table) {
+        Ok(()) => options.signal,
+        Err(NetstatCatError::Signal { .. })
+            if options.escalate_after.is_some()
+                && options.signal.terminates()
+                && options.signal != Signal::Kill =>
+        {
+            send(pid, Signal::Kill, table)?;
+            Signal::Kill
+        }
+        Err(e) => return Err(e),
+    };
+    report.sent.push(signal);
+    Ok(())
+}
+
+/// Waits for signalled processes to exit and escalates the survivors, all
+/// at once so a tree costs one grace period rather than one per process.
+/// Returns the escalation failures by index into `reports`.
+fn follow_up(
+    reports: &mut [&mut KillReport],
+    options: KillOptions,
+    table: &ProcessTable,
+) -> Vec<(usize, NetstatCatError)> {
+    let mut errors = Vec::new();
+    if !options.signal.terminates() {
+        return errors;
+    }
+
+    let escalate = options
+        .escalate_after
+        .filter(|_| options.signal != Signal::Kill);
+    wait_for_exits(reports, escalate.unwrap_or(SETTLE), table);
+    if escalate.is_none() {
+        return errors;
+    }
+
+    for (i, report) in reports.iter_mut().enumerate() {
+        if report.exited || report.sent.is_empty() {
+            continue;
+        }
+        match send(report.pid, Signal::Kill, table) {
+            Ok(()) => report.sent.push(Signal::Kill),
+            // It exited between the last poll and now.
+            Err(NetstatCatError::ProcessNotFound { .. }) => report.mark_exited(),
+            Err(e) => errors.push((i, e)),
+        }
+    }
+    wait_for_exits(reports, SETTLE, table);
+    errors
+}
+
+/// Freezes `pid` with `SIGSTOP` until [`resume`] is called. The process
+/// keeps its sockets open, so peers see it time out instead of reset.
+pub fn suspend(
+    pid: u32,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Result<(), NetstatCatError> {
+    if let Some(guard) = guard {
+        guard.check(pid)?;
+    }
+    send(pid, Signal::Stop, table)
+}
+
+/// Continues a process stopped by [`suspend`] (or any other stop signal).
+pub fn resume(pid: u32, table: &ProcessTable) -> Result<(), NetstatCatError> {
+    send(pid, Signal::Cont, table)
+}
+
+/// The tree is signalled one level at a time, and each level is waited for
+/// (and escalated) before the next one is signalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TreeOrder {
+    /// Leaves first, so no parent sees its workers die and respawns them.
+    BottomUp,
+    /// Root first, so it cannot spawn replacements while children go.
+    TopDown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeKillEntry {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    /// 0 for the root.
+    pub depth: usize,
+    pub report: KillReport,
+    pub error: Option<NetstatCatError>,
+}
+
+/// Signals `root` and all of its descendants level by level in `order`.
+/// Each level gets its own grace period, so a deep tree takes longer to
+/// escalate than a flat one. Failing to signal one process does not stop
+/// the others; see each entry's `error`.
+pub fn kill_tree(
+    root: u32,
+    order: TreeOrder,
+    options: KillOptions,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Result<Vec<TreeKillEntry>, NetstatCatError> {
+    let mut entries = process_tree(root, table)?;
+    if order == TreeOrder::BottomUp {
+        entries.reverse();
+    }
+
+    // `process_tree` is breadth first, so each depth is one contiguous run.
+    for level in entries.chunk_by_mut(|a, b| a.depth == b.depth) {
+        let pids: Vec<u32> = level.iter().map(|e| e.pid).collect();
+        for (entry, (mut report, error)) in
+            level.iter_mut().zip(kill_all(&pids, options, table, guard))
+        {
+            // Parents often exit on their own once their children are gone.
+            entry.error = match error {
+                Some(NetstatCatError::ProcessNotFound { .. }) if report.sent.is_empty() => {
+                    report.mark_exited();
+                    None
+                }
+                error => error,
+            };
+            entry.report = report;
+        }
+    }
+    Ok(entries)
+}
+
+/// Signals every PID in order and waits for them together. Failures,
+/// including protected PIDs, are reported per PID instead of stopping the
+/// batch.
+pub fn kill_all(
+    pids: &[u32],
+    options: KillOptions,
+    table: &ProcessTable,
+    guard: Option<&Guard>,
+) -> Vec<(KillReport, Option<NetstatCatError>)> {
+    let mut results: Vec<(KillReport, Option<NetstatCatError>)> = pids
+        .iter()
+        .map(|&pid| {
+            let mut report = KillReport::new(pid);
+            let sent = match guard {
+                Some(guard) => guard
+                    .check(pid)
+                    .and_then(|()| send_first(pid, options, table, &mut report)),
+                None => send_first(pid, options, table, &mut report),
+            };
+            (report, sent.err())
+        })
+        .collect();
+
+    let mut reports: Vec<&mut KillReport> = results.iter_mut().map(|(r, _)| r).collect();
+    for (i, err) in follow_up(&mut reports, options, table) {
+        results[i].1 = Some(err);
+    }
+    results
+}
+
+/// `root` and its descendants, breadth first.
+fn process_tree(root: u32, table: &ProcessTable) -> Result<Vec<TreeKillEntry>, NetstatCatError> {
+    let processes = table.all();
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (&pid, process) in &processes {
+        if let Some(parent) = process.parent {
+            children.entry(parent).or_default().push(pid);
+        }
+    }
+
+    if !processes.contains_key(&root) {
+        return Err(NetstatCatError::ProcessNotFound { pid: root });
+    }
+    let mut tree = Vec::new();
+    let mut queue = VecDeque::from([(root, 0)]);
+    while let Some((pid, depth)) = queue.pop_front() {
+        let process = &processes[&pid];
+        tree.push(TreeKillEntry {
+            pid,
+            parent_pid: process.parent,
+            name: process.name.clone(),
+            depth,
+            report: KillReport::new(pid),
+            error: None,
+        });
+        if let Some(kids) = children.get_mut(&pid) {
+            kids.sort_unstable();
+            queue.extend(kids.iter().map(|&kid| (kid, depth + 1)));
+        }
+    }
+    Ok(tree)
+}
+
+/// Only Windows needs `table`, to find the process it terminates.
+#[cfg(unix)]
+pub fn send(pid: u32, signal: Signal, _table: &ProcessTable) -> Result<(), NetstatCatError> {
+    // 0 and anything that wraps to a negative pid_t would address a whole
+    // process group (or every process) instead of one PID.
+    let target = libc::pid_t::try_from(pid)
+        .ok()
+        .filter(|&p| p > 0)
+        .ok_or_else(|| NetstatCatError::InvalidArgument(format!("Invalid PID {pid}")))?;
+
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    if unsafe { libc::kill(target, signal.number()) } == 0 {
+        Ok(())
+    } else {
+        Err(NetstatCatError::from_process_io(
+            pid,
+            &std::io::Error::last_os_error(),
+        ))
+    }
+}
+
+/// Windows has no signals. `TERM`, `INT` and `HUP` ask the process to close,
+/// like a plain `taskkill` (`WM_CLOSE` to its windows); `KILL` terminates it.
+#[cfg(not(unix))]
+pub fn send(pid: u32, signal: Signal, table: &ProcessTable) -> Result<(), NetstatCatError> {
+    let force = match signal {
+        Signal::Kill => true,
+        Signal::Term | Signal::Int | Signal::Hup => false,
+        _ => {
+            return Err(NetstatCatError::Unsupported(format!(
+                "{signal} is not supported on this platform"
+            )))
+        }
+    };
+    if !force {
+        return close(pid);
+    }
+    match table.kill(pid) {
+        Some(true) => Ok(()),
+        Some(false) => Err(NetstatCatError::Signal { pid, errno: None }),
+        None => Err(NetstatCatError::ProcessNotFound { pid }),
+    }
+}
+
+/// A plain `taskkill`. A process without a window refuses to close this
+/// way, which fails like any other refusal; [`send_first`] then escalates.
+#[cfg(not(unix))]
+fn close(pid: u32) -> Result<(), NetstatCatError> {
+    /// taskkill's exit status when no process has the PID.
+    const NOT_FOUND: i32 = 128;
+
+    let mut command = std::process::Command::new("taskkill");
+    command.args(["/PID", &pid.to_string()]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| NetstatCatError::Signal {
+        pid,
+        errno: e.raw_os_error(),
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    if output.status.code() == Some(NOT_FOUND) {
+        return Err(NetstatCatError::ProcessNotFound { pid });
+    }
+    // taskkill reports every other failure with status 1; only its message
+    // tells access denied apart.
+    if String::from_utf8_lossy(&output.stderr).contains("Access is denied") {
+        Err(NetstatCatError::PermissionDenied {
+            pid: Some(pid),
+            errno: None,
+        })
+    } else {
+        Err(NetstatCatError::Signal { pid, errno: None })
+    }
+}
+
+/// Polls until every signalled process is gone or `timeout` passes.
+fn wait_for_exits(reports: &mut [&mut KillReport], timeout: Duration, table: &ProcessTable) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut pending = false;
+        for report in reports.iter_mut() {
+            if report.exited || report.sent.is_empty() {
+                continue;
+            }
+            if table.is_alive(report.pid) {
+                pending = true;
+            } else {
+                report.mark_exited();
+            }
+        }
+
+        let now = Instant::now();
+        if !pending || now >= deadline {
+            return;
+        }
+        thread::sleep((deadline - now).min(POLL));
+    }
+}
@@ -0,0 +1,160 @@
+use std::thread;
+
+use serde::Serialize;
+use sysinfo::{
+    Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users,
+    MINIMUM_CPU_UPDATE_INTERVAL,
+};
+
+use crate::error::NetstatCatError;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+    pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    pub uid: Option<u32>,
+    pub user: Option<String>,
+    pub parent_pid: Option<u32>,
+    /// Seconds since the Unix epoch.
+    pub start_time: u64,
+    pub status: String,
+    /// Percent of one core, sampled over a short interval.
+    pub cpu_usage: f32,
+    /// Resident and virtual size in bytes.
+    pub memory: u64,
+    pub virtual_memory: u64,
+    /// Fields left empty because the OS refused to show them (typically
+    /// `exe` and `cwd` of processes owned by other users).
+    pub permission_denied: Vec<String>,
+}
+
+fn refresh(sys: &mut System, pid: Pid) {
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::everything().with_exe(UpdateKind::Always),
+    );
+}
+
+/// Why `exe` or `cwd` came back empty: the process is gone, we lack
+/// permission, or it simply has none (kernel threads have no executable).
+fn missing(pid: u32, field: &str) -> Option<NetstatCatError> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::io::ErrorKind;
+
+        match std::fs::read_link(format!("/proc/{pid}/{field}")) {
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                Some(NetstatCatError::PermissionDenied {
+                    pid: Some(pid),
+                    errno: e.raw_os_error(),
+                })
+            }
+            Err(_) if !std::path::Path::new(&format!("/proc/{pid}")).exists() => {
+                Some(NetstatCatError::ProcessNotFound { pid })
+            }
+            _ => None,
+        }
+    }
+    // Elsewhere sysinfo does not tell us why; access rights are by far the
+    // most common reason.
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = field;
+        Some(NetstatCatError::PermissionDenied {
+            pid: Some(pid),
+            errno: None,
+        })
+    }
+}
+
+/// Path of the executable, or an empty string for processes without one.
+pub fn process_path(pid: u32) -> Result<String, NetstatCatError> {
+    let mut sys = System::new();
+    refresh(&mut sys, Pid::from_u32(pid));
+    let process = sys
+        .process(Pid::from_u32(pid))
+        .ok_or(NetstatCatError::ProcessNotFound { pid })?;
+
+    match process.exe() {
+        Some(exe) => Ok(exe.to_string_lossy().into_owned()),
+        None => match missing(pid, "exe") {
+            Some(err) => Err(err),
+            None => Ok(String::new()),
+        },
+    }
+}
+
+/// Everything the detail pane shows. Blocks for
+/// [`MINIMUM_CPU_UPDATE_INTERVAL`] to measure CPU usage.
+pub fn process_details(pid: u32) -> Result<ProcessDetails, NetstatCatError> {
+    let mut sys = System::new();
+    let sys_pid = Pid::from_u32(pid);
+    refresh(&mut sys, sys_pid);
+    if sys.process(sys_pid).is_none() {
+        return Err(NetstatCatError::ProcessNotFound { pid });
+    }
+    thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    refresh(&mut sys, sys_pid);
+    let process = sys
+        .process(sys_pid)
+        .ok_or(NetstatCatError::ProcessNotFound { pid })?;
+
+    let mut permission_denied = Vec::new();
+    let mut path_field = |value: Option<&std::path::Path>, field: &str| match value {
+        Some(path) => Ok(Some(path.to_string_lossy().into_owned())),
+        None => match missing(pid, field) {
+            Some(NetstatCatError::PermissionDenied { .. }) => {
+                permission_denied.push(field.to_string());
+                Ok(None)
+            }
+            Some(err) => Err(err),
+            None => Ok(None),
+        },
+    };
+    let exe = path_field(process.exe(), "exe")?;
+    let cwd = path_field(process.cwd(), "cwd")?;
+
+    let uid = uid(process);
+    let user = process.user_id().and_then(|id| {
+        Users::new_with_refreshed_list()
+            .get_user_by_id(id)
+            .map(|user| user.name().to_string())
+    });
+
+    Ok(ProcessDetails {
+        pid,
+        name: process.name().to_string_lossy().into_owned(),
+        exe,
+        cmd: process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+        cwd,
+        uid,
+        user,
+        parent_pid: process.parent().map(|p| p.as_u32()),
+        start_time: process.start_time(),
+        status: process.status().to_string(),
+        cpu_usage: process.cpu_usage(),
+        memory: process.memory(),
+        virtual_memory: process.virtual_memory(),
+        permission_denied,
+    })
+}
+
+#[cfg(unix)]
+fn uid(process: &Process) -> Option<u32> {
+    process.user_id().map(|id| **id)
+}
+
+// Windows identifies users by SID, which has no numeric form.
+#[cfg(not(unix))]
+fn uid(_process: &Process) -> Option<u32> {
+    None
+}
@@ -1,19 +1,162 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sysinfo::ProcessStatus;
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct AddressPort {
     pub address: Option<String>,
     pub port: Option<u16>,
+    /// Reverse DNS name of `address`; only looked up for remote addresses.
+    pub hostname: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct ProcessInfo {
+    /// Stable identity of the row, see [`connection_id`].
+    pub id: String,
     pub protocol: String,
     pub local: AddressPort,
     pub remote: AddressPort,
     pub state: String,
     pub pid: u32,
     pub process_name: String,
+    /// Every process holding the socket, primary owner first.
+    pub pids: Vec<u32>,
+    /// Names matching `pids`, index for index.
+    pub process_names: Vec<String>,
+    /// Run state of the primary process; `None` if it is unknown or gone.
+    pub process_state: Option<ProcessState>,
+    /// Socket owner UID; only reported on Linux.
+    pub uid: Option<u32>,
+    /// Socket inode; only reported on Linux.
+    pub inode: Option<u32>,
+    /// The socket on the other end of a connected Unix socket.
+    pub peer: Option<PeerInfo>,
+    /// Queue sizes and kernel `tcp_info` counters, for TCP sockets on Linux.
+    pub tcp_info: Option<TcpInfo>,
+    /// Unix time in milliseconds when this connection was first observed.
+    pub first_seen: Option<u64>,
+    /// Milliseconds between `first_seen` and the sample that produced this row.
+    pub age: Option<u64>,
+    /// Unix time in milliseconds of the last observed `state` change.
+    pub state_changed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProcessState {
+    Running,
+    Sleeping,
+    /// Uninterruptible sleep, usually waiting on I/O.
+    DiskSleep,
+    Idle,
+    /// Stopped by a signal (`SIGSTOP`, `SIGTSTP`, ...).
+    Stopped,
+    /// Stopped by a debugger.
+    Tracing,
+    Zombie,
+    Dead,
+    Unknown,
+}
+
+impl From<ProcessStatus> for ProcessState {
+    fn from(status: ProcessStatus) -> Self {
+        match status {
+            ProcessStatus::Run => ProcessState::Running,
+            ProcessStatus::Sleep
+            | ProcessStatus::Waking
+            | ProcessStatus::Wakekill
+            | ProcessStatus::Parked
+            | ProcessStatus::LockBlocked => ProcessState::Sleeping,
+            ProcessStatus::UninterruptibleDiskSleep => ProcessState::DiskSleep,
+            ProcessStatus::Idle => ProcessState::Idle,
+            ProcessStatus::Stop => ProcessState::Stopped,
+            ProcessStatus::Tracing => ProcessState::Tracing,
+            ProcessStatus::Zombie => ProcessState::Zombie,
+            ProcessStatus::Dead => ProcessState::Dead,
+            ProcessStatus::Unknown(_) => ProcessState::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerInfo {
+    pub inode: u32,
+    pub pids: Vec<u32>,
+    pub process_names: Vec<String>,
+}
+
+/// Per-connection TCP internals, as shown by `ss -ti`. Queue sizes are always
+/// present; the remaining fields need `INET_DIAG_INFO` and are `None` when the
+/// sockets were read from `/proc/net/tcp*`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TcpInfo {
+    /// Bytes not yet read by the application; pending accepts for listeners.
+    pub recv_q: u32,
+    /// Bytes not yet acknowledged by the peer; the backlog size for listeners.
+    pub send_q: u32,
+    pub rtt_us: Option<u32>,
+    pub rttvar_us: Option<u32>,
+    pub cwnd: Option<u32>,
+    /// `None` while still in slow start (the kernel reports "infinity").
+    pub ssthresh: Option<u32>,
+    /// Consecutive retransmits of the current segment.
+    pub retransmits: Option<u8>,
+    /// Retransmitted segments over the connection's lifetime.
+    pub total_retrans: Option<u32>,
+    pub lost: Option<u32>,
+    pub bytes_acked: Option<u64>,
+    pub bytes_received: Option<u64>,
+    /// Bytes per second.
+    pub delivery_rate: Option<u64>,
+    /// Bytes per second.
+    pub pacing_rate: Option<u64>,
+    /// Congestion control algorithm, e.g. `cubic` or `bbr`.
+    pub congestion: Option<String>,
+}
+
+/// An address of a row with `protocol` as it is shown and matched: a
+/// wildcard Internet address (stored as `None`) is `0.0.0.0` or `[::]`, the
+/// literal a user would type. A Unix socket without a path (or peer) has none.
+pub fn display_address(protocol: &str, address: Option<&str>) -> Option<String> {
+    match address {
+        Some(address) => Some(address.to_string()),
+        None if protocol.starts_with("unix") => None,
+        None if protocol.contains('6') => Some("[::]".to_string()),
+        None => Some("0.0.0.0".to_string()),
+    }
+}
+
+/// Whether a row has a remote end. Listeners and unconnected UDP sockets
+/// report a wildcard one (`0.0.0.0:0`, or nothing at all), which is shown
+/// as `-` and exported as empty fields.
+pub fn has_remote(row: &ProcessInfo) -> bool {
+    row.remote.address.is_some() || row.remote.port.is_some_and(|port| port != 0)
+}
+
+/// Derives a row's identity from protocol, both endpoints, socket inode and
+/// primary PID. The same socket yields the same ID across samples and across
+/// runs (FNV-1a, not `DefaultHasher`, whose output may change between Rust
+/// releases), so IDs can also be compared between saved snapshots.
+pub fn connection_id(info: &ProcessInfo) -> String {
+    let key = format!(
+        "{}|{}:{}|{}:{}|{}|{}",
+        info.protocol,
+        info.local.address.as_deref().unwrap_or("*"),
+        info.local.port.map_or(String::new(), |p| p.to_string()),
+        info.remote.address.as_deref().unwrap_or("*"),
+        info.remote.port.map_or(String::new(), |p| p.to_string()),
+        info.inode.map_or(String::new(), |i| i.to_string()),
+        info.pid,
+    );
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in key.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
 }
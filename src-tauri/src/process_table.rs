@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+use sysinfo::{
+    Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, ThreadKind, UpdateKind, Users,
+};
+
+use crate::process_info::ProcessState;
+use crate::tracker;
+
+/// Above this many cached processes the table starts over. Only requested
+/// PIDs are refreshed (and evicted once dead), so processes that stop being
+/// requested would otherwise stay forever.
+const CACHE_LIMIT: usize = 4096;
+
+/// What the rest of the app needs to know about a process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessEntry {
+    pub name: String,
+    pub state: ProcessState,
+    pub parent: Option<u32>,
+    /// Owner UID; `None` on Windows, which identifies users by SID.
+    pub uid: Option<u32>,
+    /// Owner's user name, or the numeric UID if it has no name.
+    pub user: Option<String>,
+    pub kernel_thread: bool,
+}
+
+/// Timing of the last [`ProcessTable::resolve`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshStats {
+    /// Distinct PIDs asked for.
+    pub requested: usize,
+    /// How many of them exist.
+    pub resolved: usize,
+    /// Processes held in the table afterwards.
+    pub cached: usize,
+    pub elapsed_us: u64,
+    /// Unix time in milliseconds.
+    pub refreshed_at: u64,
+}
+
+struct Inner {
+    sys: System,
+    users: Users,
+    stats: RefreshStats,
+}
+
+/// sysinfo process table shared across samples. Most calls refresh just
+/// the PIDs they ask for instead of rebuilding the whole table, which
+/// is what used to dominate a sample on hosts with many processes.
+pub struct ProcessTable {
+    inner: Mutex<Inner>,
+}
+
+impl Default for ProcessTable {
+    fn default() -> Self {
+        ProcessTable {
+            inner: Mutex::new(Inner {
+                sys: System::new(),
+                users: Users::new(),
+                stats: RefreshStats::default(),
+            }),
+        }
+    }
+}
+
+impl ProcessTable {
+    /// Refreshes `pids` for a sample and returns the ones that exist. The
+    /// timing is kept for [`ProcessTable::stats`].
+    pub fn resolve(&self, pids: &[u32]) -> HashMap<u32, ProcessEntry> {
+        let started = Instant::now();
+        let wanted = distinct(pids);
+        let mut inner = self.inner.lock().unwrap();
+        let entries = inner.refresh(&wanted);
+        inner.stats = RefreshStats {
+            requested: wanted.len(),
+            resolved: entries.len(),
+            cached: inner.sys.processes().len(),
+            elapsed_us: started.elapsed().as_micros() as u64,
+            refreshed_at: tracker::now_ms(),
+        };
+        entries
+    }
+
+    /// Like [`ProcessTable::resolve`], for one-off lookups that should not
+    /// show up in the sampling stats.
+    pub fn lookup(&self, pids: &[u32]) -> HashMap<u32, ProcessEntry> {
+        self.inner.lock().unwrap().refresh(&distinct(pids))
+    }
+
+    pub fn get(&self, pid: u32) -> Option<ProcessEntry> {
+        self.lookup(&[pid]).remove(&pid)
+    }
+
+    /// Zombies count as gone: they hold no sockets and only wait for their
+    /// parent to reap them.
+    pub fn is_alive(&self, pid: u32) -> bool {
+        self.get(pid)
+            .is_some_and(|p| !matches!(p.state, ProcessState::Zombie | ProcessState::Dead))
+    }
+
+    /// Refreshes every process, for callers that need parents and children
+    /// or find their targets as they go. Threads are left out: sysinfo lists
+    /// them as children of their process, but they die with it.
+    pub fn all(&self) -> HashMap<u32, ProcessEntry> {
+        let mut inner = self.inner.lock().unwrap();
+        let Inner { sys, users, .. } = &mut *inner;
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet),
+        );
+        let mut users_refreshed = false;
+        sys.processes()
+            .iter()
+            .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+            .map(|(pid, process)| (pid.as_u32(), entry(process, users, &mut users_refreshed)))
+            .collect()
+    }
+
+    /// Terminates `pid` the way sysinfo does on this platform.
+    #[cfg(not(unix))]
+    pub fn kill(&self, pid: u32) -> Option<bool> {
+        let pid = Pid::from_u32(pid);
+        let mut inner = self.inner.lock().unwrap();
+        inner.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        inner.sys.process(pid).map(|process| process.kill())
+    }
+
+    pub fn stats(&self) -> RefreshStats {
+        self.inner.lock().unwrap().stats.clone()
+    }
+}
+
+impl Inner {
+    fn refresh(&mut self, wanted: &[Pid]) -> HashMap<u32, ProcessEntry> {
+        if self.sys.processes().len() > CACHE_LIMIT {
+            self.sys = System::new();
+        }
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(wanted),
+            true,
+            ProcessRefreshKind::nothing().with_user(UpdateKind::OnlyIfNotSet),
+        );
+
+        let mut users_refreshed = false;
+        wanted
+            .iter()
+            .filter_map(|pid| {
+                let process = self.sys.process(*pid)?;
+                Some((
+                    pid.as_u32(),
+                    entry(process, &mut self.users, &mut users_refreshed),
+                ))
+            })
+            .collect()
+    }
+}
+
+fn distinct(pids: &[u32]) -> Vec<Pid> {
+    let mut wanted: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid)).collect();
+    wanted.sort_unstable();
+    wanted.dedup();
+    wanted
+}
+
+fn entry(process: &Process, users: &mut Users, users_refreshed: &mut bool) -> ProcessEntry {
+    let user = process.user_id().map(|uid| {
+        // Load the user list lazily, and again only for a new user.
+        if users.get_user_by_id(uid).is_none() && !*users_refreshed {
+            users.refresh();
+            *users_refreshed = true;
+        }
+        users
+            .get_user_by_id(uid)
+            .map_or_else(|| uid.to_string(), |user| user.name().to_string())
+    });
+    ProcessEntry {
+        name: process.name().to_string_lossy().into_owned(),
+        state: process.status().into(),
+        parent: process.parent().map(|p| p.as_u32()),
+        uid: uid(process),
+        user,
+        kernel_thread: process.thread_kind() == Some(ThreadKind::Kernel),
+    }
+}
+
+#[cfg(unix)]
+fn uid(process: &Process) -> Option<u32> {
+    process.user_id().map(|id| **id)
+}
+
+#[cfg(not(unix))]
+fn uid(_process: &Process) -> Option<u32> {
+    None
+}
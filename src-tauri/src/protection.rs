@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NetstatCatError;
+use crate::process_table::ProcessTable;
+use crate::query::wildcard_match;
+
+/// Display servers, session managers and OS-critical services. Killing
+/// one of these ends the user's session or crashes the machine.
+const DEFAULT_PROTECTED_NAMES: &[&str] = &[
+    "Xorg",
+    "Xwayland",
+    "gnome-shell",
+    "kwin_*",
+    "plasmashell",
+    "sway",
+    "weston",
+    "gdm*",
+    "sddm",
+    "WindowServer",
+    "loginwindow",
+    "launchd",
+    "csrss.exe",
+    "dwm.exe",
+    "lsass.exe",
+    "services.exe",
+    "smss.exe",
+    "wininit.exe",
+    "winlogon.exe",
+];
+
+/// Which processes the kill commands refuse to touch unless forced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProtectionPolicy {
+    pub protect_init: bool,
+    pub protect_kernel_threads: bool,
+    /// netstat-cat itself and every process above it (shell, terminal,
+    /// session).
+    pub protect_self: bool,
+    pub allow_root_owned: bool,
+    /// Case-insensitive process names, `*` wildcards allowed.
+    pub protected_names: Vec<String>,
+}
+
+impl Default for ProtectionPolicy {
+    fn default() -> Self {
+        ProtectionPolicy {
+            protect_init: true,
+            protect_kernel_threads: true,
+            protect_self: true,
+            allow_root_owned: false,
+            protected_names: DEFAULT_PROTECTED_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl ProtectionPolicy {
+    pub fn guard<'a>(&self, table: &'a ProcessTable) -> Guard<'a> {
+        let mut lineage = HashSet::new();
+        let mut next = Some(std::process::id());
+        while let Some(pid) = next {
+            if !lineage.insert(pid) {
+                break;
+            }
+            next = table.get(pid).and_then(|p| p.parent);
+        }
+
+        Guard {
+            policy: self.clone(),
+            table,
+            lineage,
+        }
+    }
+}
+
+/// Judges PIDs against the policy as they are about to be signalled.
+pub struct Guard<'a> {
+    policy: ProtectionPolicy,
+    table: &'a ProcessTable,
+    /// Our own PID and its ancestors.
+    lineage: HashSet<u32>,
+}
+
+impl Guard<'_> {
+    /// Fails with [`NetstatCatError::Protected`] if the policy covers `pid`.
+    pub fn check(&self, pid: u32) -> Result<(), NetstatCatError> {
+        match self.reason(pid) {
+            Some(reason) => Err(NetstatCatError::Protected { pid, reason }),
+            None => Ok(()),
+        }
+    }
+
+    fn reason(&self, pid: u32) -> Option<String> {
+        let policy = &self.policy;
+        if policy.protect_init && pid == 1 {
+            return Some("it is the init process".to_string());
+        }
+        if policy.protect_self && pid == std::process::id() {
+            return Some("it is netstat-cat itself".to_string());
+        }
+        if policy.protect_self && self.lineage.contains(&pid) {
+            return Some("netstat-cat runs under it".to_string());
+        }
+
+        // PIDs that do not exist are left for the kill itself to report.
+        let process = self.table.get(pid)?;
+        if policy.protect_kernel_threads && process.kernel_thread {
+            return Some("it is a kernel thread".to_string());
+        }
+        if !policy.allow_root_owned && process.uid == Some(0) {
+            return Some("it is owned by root".to_string());
+        }
+
+        let name = process.name.to_lowercase();
+        policy
+            .protected_names
+            .iter()
+            .find(|pattern| wildcard_match(&pattern.to_lowercase(), &name))
+            .map(|pattern| format!("its name matches '{pattern}'"))
+    }
+}
+
+/// Tauri-managed holder of the current policy.
+#[derive(Default)]
+pub struct Protection {
+    policy: RwLock<ProtectionPolicy>,
+}
+
+impl Protection {
+    pub fn policy(&self) -> ProtectionPolicy {
+        self.policy.read().unwrap().clone()
+    }
+
+    pub fn set_policy(&self, policy: ProtectionPolicy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    /// The guard for a kill command, or `None` when the caller forces it.
+    pub fn guard<'a>(&self, force: bool, table: &'a ProcessTable) -> Option<Guard<'a>> {
+        (!force).then(|| self.policy.read().unwrap().guard(table))
+    }
+}
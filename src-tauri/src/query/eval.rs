@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+
+use super::lexer::Op;
+use super::parser::{Expr, Field, Value};
+use crate::process_info::{display_address, ProcessInfo};
+
+enum Actual {
+    Number(i64),
+    Text(String),
+}
+
+fn field_value(field: Field, info: &ProcessInfo) -> Option<Actual> {
+    Some(match field {
+        Field::Pid => Actual::Number(info.pid.into()),
+        Field::Protocol => Actual::Text(info.protocol.clone()),
+        Field::State => Actual::Text(info.state.clone()),
+        Field::Process => Actual::Text(info.process_name.clone()),
+        Field::LocalPort => Actual::Number(info.local.port?.into()),
+        Field::RemotePort => Actual::Number(info.remote.port?.into()),
+        // Wildcard addresses are matched as the literal the user would type.
+        Field::LocalAddress => Actual::Text(display_address(
+            &info.protocol,
+            info.local.address.as_deref(),
+        )?),
+        Field::RemoteAddress => Actual::Text(display_address(
+            &info.protocol,
+            info.remote.address.as_deref(),
+        )?),
+    })
+}
+
+impl Expr {
+    pub fn matches(&self, info: &ProcessInfo) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(info) && right.matches(info),
+            Expr::Or(left, right) => left.matches(info) || right.matches(info),
+            Expr::Not(inner) => !inner.matches(info),
+            Expr::Compare { field, op, value } => match field_value(*field, info) {
+                // A missing value (e.g. the remote port of a UDP socket)
+                // never matches, not even `!=`.
+                None => false,
+                Some(actual) => compare(&actual, *op, value),
+            },
+        }
+    }
+}
+
+/// Mirrors the JavaScript semantics of the original filter: string values
+/// compare case-insensitively as strings (with `*` wildcards for `=`), number
+/// values compare numerically when the field is numeric.
+fn compare(actual: &Actual, op: Op, value: &Value) -> bool {
+    let ordering = match (actual, value) {
+        (Actual::Number(a), Value::Number(b)) => a.cmp(b),
+        (Actual::Text(a), Value::Number(b)) => match a.parse::<i64>() {
+            Ok(a) => a.cmp(b),
+            Err(_) => {
+                return match op {
+                    Op::Eq => false,
+                    Op::NotEq => true,
+                    _ => false,
+                }
+            }
+        },
+        (actual, Value::Text(pattern)) => {
+            let text = match actual {
+                Actual::Number(n) => n.to_string(),
+                Actual::Text(t) => t.to_lowercase(),
+            };
+            let pattern = pattern.to_lowercase();
+            if op == Op::Eq && pattern.contains('*') {
+                return wildcard_match(&pattern, &text);
+            }
+            text.as_str().cmp(pattern.as_str())
+        }
+    };
+
+    match op {
+        Op::Eq => ordering == Ordering::Equal,
+        Op::NotEq => ordering != Ordering::Equal,
+        Op::Gt => ordering == Ordering::Greater,
+        Op::Lt => ordering == Ordering::Less,
+        Op::GtEq => ordering != Ordering::Less,
+        Op::LtEq => ordering != Ordering::Greater,
+    }
+}
+
+/// Whole-string match where `*` stands for any run of characters.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No '*' at all: the prefix had to be the whole text.
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
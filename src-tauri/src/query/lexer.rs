@@ -0,0 +1,140 @@
+use super::{QueryError, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    NotEq,
+    Gt,
+    Lt,
+    GtEq,
+    LtEq,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    Number(i64),
+    Str(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '*' | '-' | '.')
+}
+
+/// Splits a query into tokens. Positions are counted in characters so the
+/// frontend can map them straight onto the input box.
+pub fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let next = chars.get(pos + 1).copied();
+        let kind = match c {
+            '(' => {
+                pos += 1;
+                TokenKind::LParen
+            }
+            ')' => {
+                pos += 1;
+                TokenKind::RParen
+            }
+            '!' if next == Some('=') => {
+                pos += 2;
+                TokenKind::Op(Op::NotEq)
+            }
+            '!' => {
+                pos += 1;
+                TokenKind::Not
+            }
+            '&' if next == Some('&') => {
+                pos += 2;
+                TokenKind::And
+            }
+            '|' if next == Some('|') => {
+                pos += 2;
+                TokenKind::Or
+            }
+            '=' | ':' => {
+                // `==` is accepted as a courtesy; `:` is an alias for `=`.
+                pos += if c == '=' && next == Some('=') { 2 } else { 1 };
+                TokenKind::Op(Op::Eq)
+            }
+            '>' | '<' => {
+                let or_equal = next == Some('=');
+                pos += if or_equal { 2 } else { 1 };
+                TokenKind::Op(match (c, or_equal) {
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::GtEq,
+                    ('<', false) => Op::Lt,
+                    _ => Op::LtEq,
+                })
+            }
+            '"' | '\'' => {
+                let close = chars[pos + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| {
+                        QueryError::new("unterminated string", Span::new(start, chars.len()))
+                    })?;
+                let value: String = chars[pos + 1..pos + 1 + close].iter().collect();
+                pos += close + 2;
+                TokenKind::Str(value)
+            }
+            c if is_word_char(c) => {
+                while pos < chars.len() && is_word_char(chars[pos]) {
+                    pos += 1;
+                }
+                let text: String = chars[start..pos].iter().collect();
+                if text.eq_ignore_ascii_case("and") {
+                    TokenKind::And
+                } else if text.eq_ignore_ascii_case("or") {
+                    TokenKind::Or
+                } else if text.chars().all(|ch| ch.is_ascii_digit()) {
+                    // Too large for a number: still usable as a string value.
+                    text.parse()
+                        .map_or(TokenKind::Ident(text), TokenKind::Number)
+                } else {
+                    TokenKind::Ident(text)
+                }
+            }
+            c => {
+                return Err(QueryError::new(
+                    format!("unexpected character '{c}'"),
+                    Span::new(start, start + 1),
+                ))
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, pos),
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(chars.len(), chars.len()),
+    });
+    Ok(tokens)
+}
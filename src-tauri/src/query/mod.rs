@@ -0,0 +1,122 @@
+//! Filter language shared by the search box and the CLI. The grammar and
+//! semantics follow `src/utils/queryParser.ts` and `filters_en.md`.
+
+mod eval;
+mod lexer;
+mod parser;
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::process_info::ProcessInfo;
+pub use eval::wildcard_match;
+pub use lexer::Op;
+pub use parser::{parse, Expr, Field, Value};
+
+/// Character offsets into the query, end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl QueryError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        QueryError {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// What the search box accepts: a semantic query, a `min-max` range over
+/// ports and PID, or plain text (with optional `*` wildcards).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Query(Expr),
+    Range(u32, u32),
+    Text(String),
+}
+
+impl Filter {
+    /// Input containing operator characters is parsed as a query and its
+    /// errors are reported; anything else is a simple search.
+    pub fn parse(input: &str) -> Result<Filter, QueryError> {
+        let input = input.trim();
+        if input.contains(['=', ':', '<', '>', '!', '&', '|', '(', ')']) {
+            return parse(input).map(Filter::Query);
+        }
+
+        let lower = input.to_lowercase();
+        if let Some((min, max)) = lower.split_once('-') {
+            if let (Ok(min), Ok(max)) = (min.parse(), max.parse()) {
+                return Ok(Filter::Range(min, max));
+            }
+        }
+        Ok(Filter::Text(lower))
+    }
+
+    pub fn matches(&self, info: &ProcessInfo) -> bool {
+        match self {
+            Filter::Query(expr) => expr.matches(info),
+            Filter::Range(min, max) => {
+                let in_range = |n: u32| (*min..=*max).contains(&n);
+                info.local.port.is_some_and(|p| in_range(p.into()))
+                    || in_range(info.remote.port.unwrap_or(0).into())
+                    || in_range(info.pid)
+            }
+            Filter::Text(text) if text.is_empty() => true,
+            Filter::Text(text) => {
+                let candidates = [
+                    Some(info.process_name.to_lowercase()),
+                    Some(info.pid.to_string()),
+                    info.local.port.map(|p| p.to_string()),
+                    info.remote.port.map(|p| p.to_string()),
+                    Some(info.state.to_lowercase()),
+                    info.uid.map(|u| u.to_string()),
+                ];
+                candidates.iter().flatten().any(|value| {
+                    if text.contains('*') {
+                        eval::wildcard_match(text, value)
+                    } else {
+                        value.contains(text.as_str())
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Keeps the rows matching `filter`.
+pub fn filter_rows(rows: Vec<ProcessInfo>, filter: &Filter) -> Vec<ProcessInfo> {
+    rows.into_iter().filter(|row| filter.matches(row)).collect()
+}
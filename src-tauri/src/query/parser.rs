@@ -0,0 +1,154 @@
+use super::lexer::{tokenize, Op, Token, TokenKind};
+use super::{QueryError, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Pid,
+    Protocol,
+    State,
+    Process,
+    LocalPort,
+    RemotePort,
+    LocalAddress,
+    RemoteAddress,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "pid" => Field::Pid,
+            "proto" | "protocol" => Field::Protocol,
+            "state" => Field::State,
+            "process" | "name" | "processname" => Field::Process,
+            "lport" | "localport" => Field::LocalPort,
+            "rport" | "remoteport" => Field::RemotePort,
+            "laddr" | "localaddress" | "local" => Field::LocalAddress,
+            "raddr" | "remoteaddress" | "remote" => Field::RemoteAddress,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(i64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare { field: Field, op: Op, value: Value },
+}
+
+/// Recursive-descent parser, same grammar as `src/utils/queryParser.ts`:
+///
+/// ```text
+/// expr       := and_term { OR and_term }
+/// and_term   := factor { AND factor }
+/// factor     := "(" expr ")" | NOT factor | comparison
+/// comparison := IDENT OP (NUMBER | STRING | IDENT)
+/// ```
+pub fn parse(input: &str) -> Result<Expr, QueryError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expression()?;
+
+    let trailing = parser.peek();
+    if trailing.kind != TokenKind::Eof {
+        let message = if trailing.kind == TokenKind::RParen {
+            "unmatched ')'"
+        } else {
+            "expected '&&' or '||' before this"
+        };
+        return Err(QueryError::new(message, trailing.span));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expression(&mut self) -> Result<Expr, QueryError> {
+        let mut node = self.and_term()?;
+        while self.peek().kind == TokenKind::Or {
+            self.advance();
+            node = Expr::Or(Box::new(node), Box::new(self.and_term()?));
+        }
+        Ok(node)
+    }
+
+    fn and_term(&mut self) -> Result<Expr, QueryError> {
+        let mut node = self.factor()?;
+        while self.peek().kind == TokenKind::And {
+            self.advance();
+            node = Expr::And(Box::new(node), Box::new(self.factor()?));
+        }
+        Ok(node)
+    }
+
+    fn factor(&mut self) -> Result<Expr, QueryError> {
+        match self.peek().kind {
+            TokenKind::LParen => {
+                let open = self.advance();
+                let node = self.expression()?;
+                let close = self.advance();
+                if close.kind != TokenKind::RParen {
+                    return Err(QueryError::new(
+                        "expected ')' to close this group",
+                        Span::new(open.span.start, close.span.end.max(open.span.end)),
+                    ));
+                }
+                Ok(node)
+            }
+            TokenKind::Not => {
+                self.advance();
+                Ok(Expr::Not(Box::new(self.factor()?)))
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, QueryError> {
+        let field_token = self.advance();
+        let TokenKind::Ident(name) = &field_token.kind else {
+            return Err(QueryError::new("expected a field name", field_token.span));
+        };
+        let field = Field::from_name(name)
+            .ok_or_else(|| QueryError::new(format!("unknown field '{name}'"), field_token.span))?;
+
+        let op_token = self.advance();
+        let TokenKind::Op(op) = op_token.kind else {
+            return Err(QueryError::new(
+                format!("expected an operator after '{name}'"),
+                op_token.span,
+            ));
+        };
+
+        let value_token = self.advance();
+        let value = match value_token.kind {
+            TokenKind::Number(n) => Value::Number(n),
+            TokenKind::Str(s) | TokenKind::Ident(s) => Value::Text(s),
+            _ => return Err(QueryError::new("expected a value", value_token.span)),
+        };
+
+        Ok(Expr::Compare { field, op, value })
+    }
+}
@@ -0,0 +1,132 @@
+use super::*;
+use crate::process_info::AddressPort;
+
+fn item() -> ProcessInfo {
+    ProcessInfo {
+        protocol: "tcp".into(),
+        local: AddressPort {
+            address: Some("127.0.0.1".into()),
+            port: Some(8080),
+            hostname: None,
+        },
+        remote: AddressPort {
+            address: Some("1.1.1.1".into()),
+            port: Some(443),
+            hostname: None,
+        },
+        state: "ESTABLISHED".into(),
+        pid: 1234,
+        process_name: "chrome.exe".into(),
+        ..Default::default()
+    }
+}
+
+fn listen() -> ProcessInfo {
+    ProcessInfo {
+        protocol: "tcp".into(),
+        local: AddressPort {
+            address: None,
+            port: Some(80),
+            hostname: None,
+        },
+        remote: AddressPort {
+            address: None,
+            port: Some(0),
+            hostname: None,
+        },
+        state: "LISTEN".into(),
+        pid: 4,
+        process_name: "System".into(),
+        ..Default::default()
+    }
+}
+
+fn query(input: &str) -> Expr {
+    parse(input).unwrap_or_else(|e| panic!("{input}: {e}"))
+}
+
+#[test]
+fn validates_correct_queries() {
+    assert!(parse("pid=123").is_ok());
+    assert!(parse("pid = 123").is_ok());
+    assert!(parse("process=\"chrome\"").is_ok());
+    assert!(parse("pid=123 && state=LISTEN").is_ok());
+    assert!(parse("invalid").is_err());
+}
+
+#[test]
+fn filters_by_pid() {
+    let filter = query("pid=1234");
+    assert!(filter.matches(&item()));
+    assert!(!filter.matches(&listen()));
+}
+
+#[test]
+fn filters_by_process_name() {
+    assert!(query("process=\"chrome.exe\"").matches(&item()));
+    assert!(query("process=System").matches(&listen()));
+}
+
+#[test]
+fn filters_by_port() {
+    assert!(query("lport=8080").matches(&item()));
+    assert!(!query("lport=9999").matches(&item()));
+    assert!(query("rport=443").matches(&item()));
+}
+
+#[test]
+fn logical_operators_and_grouping() {
+    assert!(query("pid=1234 && protocol=tcp").matches(&item()));
+    assert!(!query("pid=1234 && protocol=udp").matches(&item()));
+
+    let either = query("pid=9999 || pid=1234");
+    assert!(either.matches(&item()));
+    assert!(!either.matches(&listen()));
+
+    let grouped = query("(pid=1234 || pid=4) && state=LISTEN");
+    assert!(!grouped.matches(&item()));
+    assert!(grouped.matches(&listen()));
+
+    let negated = query("!state=LISTEN");
+    assert!(negated.matches(&item()));
+    assert!(!negated.matches(&listen()));
+}
+
+#[test]
+fn comparisons_wildcards_and_case() {
+    assert!(query("pid > 1000").matches(&item()));
+    assert!(!query("pid < 100").matches(&item()));
+    assert!(query("process=chrom*").matches(&item()));
+    assert!(query("process=*exe").matches(&item()));
+    assert!(query("STATE=established").matches(&item()));
+    assert!(query("laddr=0.0.0.0").matches(&listen()));
+}
+
+#[test]
+fn missing_values_never_match() {
+    let mut udp = item();
+    udp.remote.port = None;
+    assert!(!query("rport=443").matches(&udp));
+    assert!(!query("rport!=443").matches(&udp));
+}
+
+#[test]
+fn errors_carry_spans() {
+    assert_eq!(
+        parse("pid=1 && bogus=2").unwrap_err().span,
+        Span::new(9, 14)
+    );
+    assert_eq!(parse("(pid=1").unwrap_err().span, Span::new(0, 6));
+    assert_eq!(parse("pid=1)").unwrap_err().span, Span::new(5, 6));
+    assert_eq!(parse("pid=\"1").unwrap_err().span, Span::new(4, 6));
+}
+
+#[test]
+fn simple_search_fallback() {
+    assert_eq!(Filter::parse("80-443"), Ok(Filter::Range(80, 443)));
+    assert!(Filter::parse("80-443").unwrap().matches(&listen()));
+    assert!(Filter::parse("chrome").unwrap().matches(&item()));
+    assert!(Filter::parse("chr*exe").unwrap().matches(&item()));
+    assert!(!Filter::parse("firefox").unwrap().matches(&item()));
+    assert!(Filter::parse("pid=").is_err());
+}
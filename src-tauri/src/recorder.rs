@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::changes::diff_rows;
+use crate::error::NetstatCatError;
+use crate::history::{History, Retention};
+use crate::sampler::Sampler;
+use crate::tracker;
+
+/// Emitted with a [`NetstatCatError`] when a sample cannot be recorded.
+pub const ERROR_EVENT: &str = "history-error";
+
+pub const DEFAULT_INTERVAL_MS: u64 = 5000;
+const MIN_INTERVAL_MS: u64 = 1000;
+
+/// The recorder's name among the [`Sampler`]'s consumers.
+const CONSUMER: &str = "recorder";
+
+/// Tauri-managed connection history. Recording is off until started; the
+/// database is opened on first use and stays open for queries.
+pub struct Recorder {
+    path: PathBuf,
+    history: Mutex<Option<Arc<History>>>,
+}
+
+impl Recorder {
+    pub fn new(path: PathBuf) -> Self {
+        Recorder {
+            path,
+            history: Mutex::new(None),
+        }
+    }
+
+    pub fn history(&self) -> Result<Arc<History>, NetstatCatError> {
+        let mut history = self.history.lock().unwrap();
+        if let Some(history) = &*history {
+            return Ok(history.clone());
+        }
+        let opened = Arc::new(History::open(&self.path)?);
+        *history = Some(opened.clone());
+        Ok(opened)
+    }
+
+    /// Starts (or restarts) recording every `interval`.
+    pub fn start(
+        &self,
+        sampler: &Sampler,
+        interval: Duration,
+        retention: Retention,
+    ) -> Result<(), NetstatCatError> {
+        self.stop(sampler);
+        let history = self.history()?;
+        history.close_dangling()?;
+
+        let interval = interval.max(Duration::from_millis(MIN_INTERVAL_MS));
+        let mut previous = HashMap::new();
+        let mut sequence = 0;
+        sampler.subscribe(CONSUMER, interval, ERROR_EVENT, move |_, rows| {
+            sequence += 1;
+            let diff = diff_rows(&mut previous, rows, sequence, false);
+            history
+                .record(&diff, tracker::now_ms(), retention)
+                .inspect_err(|_| {
+                    // The diff is lost; start over from a fresh baseline.
+                    previous.clear();
+                    let _ = history.close_dangling();
+                })
+        });
+        Ok(())
+    }
+
+    pub fn stop(&self, sampler: &Sampler) {
+        sampler.unsubscribe(CONSUMER);
+    }
+
+    pub fn is_recording(&self, sampler: &Sampler) -> bool {
+        sampler.is_subscribed(CONSUMER)
+    }
+}
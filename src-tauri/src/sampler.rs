@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::commands;
+use crate::error::NetstatCatError;
+use crate::process_info::ProcessInfo;
+
+type OnSample = Box<dyn FnMut(&AppHandle, Vec<ProcessInfo>) -> Result<(), NetstatCatError> + Send>;
+
+struct Consumer {
+    interval: Duration,
+    error_event: &'static str,
+    due: Instant,
+    on_sample: OnSample,
+}
+
+#[derive(Default)]
+struct State {
+    consumers: HashMap<&'static str, Consumer>,
+    running: bool,
+}
+
+/// Tauri-managed background thread that samples the connections for every
+/// consumer at once: the live view and the history recorder share one scan
+/// per tick instead of running their own. Each consumer still gets samples
+/// at its own interval. The thread runs while anyone is subscribed.
+pub struct Sampler {
+    app: AppHandle,
+    state: Arc<(Mutex<State>, Condvar)>,
+}
+
+impl Sampler {
+    pub fn new(app: AppHandle) -> Self {
+        Sampler {
+            app,
+            state: Arc::default(),
+        }
+    }
+
+    /// Adds `name`, replacing an earlier consumer of that name, and samples
+    /// for it right away. Each sample's rows go to `on_sample`; scan errors
+    /// and those it returns are emitted as `error_event`.
+    pub fn subscribe(
+        &self,
+        name: &'static str,
+        interval: Duration,
+        error_event: &'static str,
+        on_sample: impl FnMut(&AppHandle, Vec<ProcessInfo>) -> Result<(), NetstatCatError>
+            + Send
+            + 'static,
+    ) {
+        let (lock, wake) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.consumers.insert(
+            name,
+            Consumer {
+                interval,
+                error_event,
+                due: Instant::now(),
+                on_sample: Box::new(on_sample),
+            },
+        );
+        if !state.running {
+            state.running = true;
+            let app = self.app.clone();
+            let shared = self.state.clone();
+            thread::spawn(move || run(app, shared));
+        }
+        wake.notify_all();
+    }
+
+    /// Removes `name`. Once this returns, its `on_sample` is not called
+    /// again; a call in progress is waited for.
+    pub fn unsubscribe(&self, name: &str) {
+        self.state.0.lock().unwrap().consumers.remove(name);
+    }
+
+    pub fn is_subscribed(&self, name: &str) -> bool {
+        self.state.0.lock().unwrap().consumers.contains_key(name)
+    }
+}
+
+fn run(app: AppHandle, shared: Arc<(Mutex<State>, Condvar)>) {
+    let (lock, wake) = &*shared;
+    loop {
+        // Sleep until the first consumer is due, waking early for new ones.
+        let mut state = lock.lock().unwrap();
+        loop {
+            let Some(due) = state.consumers.values().map(|c| c.due).min() else {
+                state.running = false;
+                return;
+            };
+            let now = Instant::now();
+            if due <= now {
+                break;
+            }
+            state = wake.wait_timeout(state, due - now).unwrap().0;
+        }
+        drop(state);
+
+        let started = Instant::now();
+        let result = commands::scan(&app);
+
+        // Consumers that left during the scan no longer get its rows.
+        let mut state = lock.lock().unwrap();
+        for consumer in state.consumers.values_mut() {
+            if consumer.due > started {
+                continue;
+            }
+            consumer.due = started + consumer.interval;
+            let result = match &result {
+                Ok(rows) => (consumer.on_sample)(&app, rows.clone()),
+                // Someone else abandoned the scan we joined; try next tick.
+                Err(NetstatCatError::Cancelled) => Ok(()),
+                Err(e) => Err(e.clone()),
+            };
+            if let Err(e) = result {
+                let _ = app.emit(consumer.error_event, &e);
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+
+use crate::error::NetstatCatError;
+use crate::process_info::ProcessInfo;
+
+type ScanResult = Result<Vec<ProcessInfo>, NetstatCatError>;
+
+#[derive(Default)]
+struct State {
+    /// Generation of the newest scan started.
+    started: u64,
+    /// Generation of the scan callers can still join, if one is running.
+    in_flight: Option<u64>,
+    /// Newest finished scan and its result.
+    finished: u64,
+    result: Option<ScanResult>,
+}
+
+/// Coalesces overlapping scans: whoever asks while a scan is running waits
+/// for that scan instead of starting another one. Blocks, so call it from
+/// a blocking thread.
+#[derive(Default)]
+pub struct Scanner {
+    state: Mutex<State>,
+    done: Condvar,
+    /// Scans up to this generation should give up.
+    cancelled: AtomicU64,
+}
+
+/// Handed to the scan so it can stop early once it is no longer wanted.
+pub struct CancelToken<'a> {
+    scanner: &'a Scanner,
+    generation: u64,
+}
+
+impl CancelToken<'_> {
+    pub fn is_cancelled(&self) -> bool {
+        self.scanner.cancelled.load(Ordering::Relaxed) >= self.generation
+    }
+}
+
+impl Scanner {
+    /// Runs `scan`, or joins the one already running.
+    pub fn scan(&self, scan: impl FnOnce(&CancelToken) -> ScanResult) -> ScanResult {
+        let mut state = self.state.lock().unwrap();
+        if let Some(generation) = state.in_flight {
+            let state = self
+                .done
+                .wait_while(state, |s| s.finished < generation)
+                .unwrap();
+            return state.result.clone().expect("a finished scan has a result");
+        }
+
+        state.started += 1;
+        let generation = state.started;
+        state.in_flight = Some(generation);
+        drop(state);
+
+        let token = CancelToken {
+            scanner: self,
+            generation,
+        };
+        let result = match scan(&token) {
+            Ok(_) if token.is_cancelled() => Err(NetstatCatError::Cancelled),
+            result => result,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.in_flight == Some(generation) {
+            state.in_flight = None;
+        }
+        // A cancelled scan can finish after the one that replaced it.
+        if generation > state.finished {
+            state.finished = generation;
+            state.result = Some(result.clone());
+        }
+        self.done.notify_all();
+        result
+    }
+
+    /// Makes the running scan (if any) stop at its next checkpoint and fail
+    /// with [`NetstatCatError::Cancelled`]. The next request starts afresh
+    /// instead of joining it.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(generation) = state.in_flight.take() {
+            self.cancelled.fetch_max(generation, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn rows(id: &str) -> Vec<ProcessInfo> {
+        vec![ProcessInfo {
+            id: id.into(),
+            ..ProcessInfo::default()
+        }]
+    }
+
+    /// A scan that reports when it has started and then blocks until
+    /// released, checking its token only at the end.
+    fn blocked_scan<'a>(
+        id: &'static str,
+        calls: &'a AtomicUsize,
+    ) -> (
+        impl FnOnce(&CancelToken) -> ScanResult + 'a,
+        mpsc::Receiver<()>,
+        mpsc::Sender<()>,
+    ) {
+        let (started_tx, started) = mpsc::channel();
+        let (release, release_rx) = mpsc::channel::<()>();
+        let scan = move |_: &CancelToken| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            Ok(rows(id))
+        };
+        (scan, started, release)
+    }
+
+    #[test]
+    fn joins_the_scan_in_flight() {
+        let scanner = Scanner::default();
+        let calls = AtomicUsize::new(0);
+        let (scan, started, release) = blocked_scan("first", &calls);
+
+        thread::scope(|s| {
+            let first = s.spawn(|| scanner.scan(scan));
+            started.recv().unwrap();
+            let joined = s.spawn(|| {
+                scanner.scan(|_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(rows("second"))
+                })
+            });
+            // Give the second caller time to start waiting.
+            thread::sleep(Duration::from_millis(50));
+            release.send(()).unwrap();
+
+            assert_eq!(first.join().unwrap(), Ok(rows("first")));
+            assert_eq!(joined.join().unwrap(), Ok(rows("first")));
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Once finished, the next caller scans afresh.
+        assert_eq!(scanner.scan(|_| Ok(rows("third"))), Ok(rows("third")));
+    }
+
+    #[test]
+    fn cancelled_scan_finishing_late_does_not_win() {
+        let scanner = Scanner::default();
+        let calls = AtomicUsize::new(0);
+        let (scan, started, release) = blocked_scan("cancelled", &calls);
+
+        thread::scope(|s| {
+            let cancelled = s.spawn(|| scanner.scan(scan));
+            started.recv().unwrap();
+            let joined = s.spawn(|| scanner.scan(|_| Ok(rows("unused"))));
+            thread::sleep(Duration::from_millis(50));
+
+            scanner.cancel();
+            // The replacement does not join the cancelled scan, and whoever
+            // was waiting on that one gets the replacement's result.
+            let replacement = scanner.scan(|token| {
+                assert!(!token.is_cancelled());
+                Ok(rows("replacement"))
+            });
+            assert_eq!(replacement, Ok(rows("replacement")));
+            assert_eq!(joined.join().unwrap(), Ok(rows("replacement")));
+
+            release.send(()).unwrap();
+            assert_eq!(cancelled.join().unwrap(), Err(NetstatCatError::Cancelled));
+        });
+
+        let state = scanner.state.lock().unwrap();
+        assert_eq!(state.finished, 2);
+        assert_eq!(state.result, Some(Ok(rows("replacement"))));
+    }
+
+    #[test]
+    fn cancel_with_nothing_running() {
+        let scanner = Scanner::default();
+        scanner.cancel();
+        let result = scanner.scan(|token| {
+            assert!(!token.is_cancelled());
+            Ok(rows("after"))
+        });
+        assert_eq!(result, Ok(rows("after")));
+    }
+}
@@ -0,0 +1,279 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+use crate::error::NetstatCatError;
+use crate::process_info::ProcessInfo;
+use crate::tracker;
+
+const FORMAT: &str = "netstat-cat-snapshot";
+/// Bump when a change would make older readers misread a snapshot. Added
+/// fields do not count: missing ones load as their defaults.
+pub const FORMAT_VERSION: u32 = 1;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Where and when a snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMeta {
+    pub hostname: Option<String>,
+    /// e.g. `Linux 24.04 Ubuntu`.
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    /// Unix time in milliseconds.
+    pub taken_at: u64,
+    /// netstat-cat version that wrote it.
+    pub tool_version: String,
+    /// The tool whose output was imported (`ss`, `netstat` or `lsof`);
+    /// `None` for snapshots netstat-cat took itself.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub meta: SnapshotMeta,
+    pub connections: Vec<ProcessInfo>,
+}
+
+/// On disk: gzip-compressed JSON, `{ format, version, meta, connections }`.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    format: &'a str,
+    version: u32,
+    #[serde(flatten)]
+    snapshot: &'a Snapshot,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+fn io_err(path: &Path, err: std::io::Error) -> NetstatCatError {
+    NetstatCatError::Io {
+        message: format!("{}: {err}", path.display()),
+        errno: err.raw_os_error(),
+    }
+}
+
+fn invalid(path: &Path, reason: impl std::fmt::Display) -> NetstatCatError {
+    NetstatCatError::InvalidArgument(format!(
+        "{} is not a netstat-cat snapshot: {reason}",
+        path.display()
+    ))
+}
+
+impl Snapshot {
+    /// Wraps `connections` with this host's metadata.
+    pub fn capture(connections: Vec<ProcessInfo>) -> Self {
+        Snapshot {
+            meta: SnapshotMeta {
+                hostname: System::host_name(),
+                os: System::long_os_version(),
+                kernel: System::kernel_version(),
+                taken_at: tracker::now_ms(),
+                tool_version: env!("CARGO_PKG_VERSION").to_string(),
+                source: None,
+            },
+            connections,
+        }
+    }
+
+    /// A file name like `netstat-cat-myhost-1712345678901.json.gz`.
+    pub fn file_name(&self) -> String {
+        let host: String = self
+            .meta
+            .hostname
+            .as_deref()
+            .unwrap_or("host")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("netstat-cat-{host}-{}.json.gz", self.meta.taken_at)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), NetstatCatError> {
+        let file = File::create(path).map_err(|e| io_err(path, e))?;
+        let mut gz = GzEncoder::new(BufWriter::new(file), Compression::default());
+        let envelope = Envelope {
+            format: FORMAT,
+            version: FORMAT_VERSION,
+            snapshot: self,
+        };
+        serde_json::to_writer(&mut gz, &envelope)
+            .map_err(std::io::Error::from)
+            .and_then(|()| gz.finish())
+            .and_then(|mut out| out.flush())
+            .map_err(|e| io_err(path, e))
+    }
+
+    /// Reads a snapshot written by [`Snapshot::save`]; plain (uncompressed)
+    /// JSON is accepted too, so a snapshot can be edited by hand.
+    pub fn load(path: &Path) -> Result<Snapshot, NetstatCatError> {
+        let raw = std::fs::read(path).map_err(|e| io_err(path, e))?;
+        let json = if raw.starts_with(&GZIP_MAGIC) {
+            let mut json = Vec::new();
+            GzDecoder::new(raw.as_slice())
+                .read_to_end(&mut json)
+                .map_err(|e| invalid(path, e))?;
+            json
+        } else {
+            raw
+        };
+
+        let header: Header = serde_json::from_slice(&json).map_err(|e| invalid(path, e))?;
+        if header.format != FORMAT {
+            return Err(invalid(path, format!("unknown format '{}'", header.format)));
+        }
+        if header.version > FORMAT_VERSION {
+            return Err(NetstatCatError::Unsupported(format!(
+                "{} was written by a newer netstat-cat (format version {}, this one reads up to {FORMAT_VERSION})",
+                path.display(),
+                header.version
+            )));
+        }
+        serde_json::from_slice(&json).map_err(|e| invalid(path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::process_info::AddressPort;
+
+    /// A file in the temp directory, removed again when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(
+                std::env::temp_dir()
+                    .join(format!("netstat-cat-test-{}-{name}", std::process::id())),
+            )
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            meta: SnapshotMeta {
+                hostname: Some("build box".into()),
+                os: Some("Linux".into()),
+                kernel: None,
+                taken_at: 1_712_345_678_901,
+                tool_version: "0.1.0".into(),
+                source: None,
+            },
+            connections: vec![ProcessInfo {
+                id: "abc".into(),
+                protocol: "tcp".into(),
+                local: AddressPort {
+                    address: Some("127.0.0.1".into()),
+                    port: Some(8080),
+                    hostname: None,
+                },
+                state: "LISTEN".into(),
+                pid: 42,
+                process_name: "server".into(),
+                pids: vec![42],
+                process_names: vec!["server".into()],
+                ..ProcessInfo::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let file = TempFile::new("round-trip.json.gz");
+        let snapshot = snapshot();
+        snapshot.save(&file.0).unwrap();
+
+        let raw = std::fs::read(&file.0).unwrap();
+        assert!(raw.starts_with(&GZIP_MAGIC));
+        assert_eq!(Snapshot::load(&file.0).unwrap(), snapshot);
+        assert_eq!(
+            snapshot.file_name(),
+            "netstat-cat-build_box-1712345678901.json.gz"
+        );
+    }
+
+    #[test]
+    fn loads_plain_json() {
+        let file = TempFile::new("plain.json");
+        // Hand-written: no `source`, and fields left out of the row.
+        std::fs::write(
+            &file.0,
+            r#"{
+                "format": "netstat-cat-snapshot",
+                "version": 1,
+                "meta": { "hostname": null, "os": null, "kernel": null,
+                          "takenAt": 5, "toolVersion": "0.0.1" },
+                "connections": [{
+                    "id": "x", "protocol": "udp",
+                    "local": { "address": null, "port": 53 },
+                    "remote": { "address": null, "port": null },
+                    "state": "", "pid": 1, "processName": "dns",
+                    "pids": [1], "processNames": ["dns"]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let snapshot = Snapshot::load(&file.0).unwrap();
+        assert_eq!(snapshot.meta.taken_at, 5);
+        assert_eq!(snapshot.meta.source, None);
+        assert_eq!(snapshot.connections.len(), 1);
+        assert_eq!(snapshot.connections[0].local.port, Some(53));
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_other_files() {
+        let file = TempFile::new("newer.json");
+        std::fs::write(
+            &file.0,
+            format!(
+                r#"{{ "format": "netstat-cat-snapshot", "version": {}, "whatever": true }}"#,
+                FORMAT_VERSION + 1
+            ),
+        )
+        .unwrap();
+        assert!(matches!(
+            Snapshot::load(&file.0),
+            Err(NetstatCatError::Unsupported(_))
+        ));
+
+        std::fs::write(&file.0, r#"{ "format": "something-else", "version": 1 }"#).unwrap();
+        assert!(matches!(
+            Snapshot::load(&file.0),
+            Err(NetstatCatError::InvalidArgument(_))
+        ));
+
+        std::fs::write(&file.0, [0x1f, 0x8b, 0, 0]).unwrap();
+        assert!(matches!(
+            Snapshot::load(&file.0),
+            Err(NetstatCatError::InvalidArgument(_))
+        ));
+    }
+}
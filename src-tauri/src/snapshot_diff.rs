@@ -0,0 +1,496 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::process_info::{AddressPort, ProcessInfo};
+use crate::snapshot::{Snapshot, SnapshotMeta};
+
+/// A listener present on both sides, held by other processes afterwards
+/// (typically a service restarted by a deploy).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PidChange {
+    pub protocol: String,
+    pub local: AddressPort,
+    pub before_pids: Vec<u32>,
+    pub before_names: Vec<String>,
+    pub after_pids: Vec<u32>,
+    pub after_names: Vec<String>,
+}
+
+/// A connection present on both sides in a different state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChange {
+    /// The connection as it is afterwards.
+    pub connection: ProcessInfo,
+    pub from: String,
+    pub to: String,
+}
+
+/// Remote addresses a process talks to afterwards but did not before.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPeers {
+    pub process_name: String,
+    pub pids: Vec<u32>,
+    pub peers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    pub before: SnapshotMeta,
+    pub after: SnapshotMeta,
+    pub new_listeners: Vec<ProcessInfo>,
+    pub closed_listeners: Vec<ProcessInfo>,
+    pub pid_changes: Vec<PidChange>,
+    pub state_changes: Vec<StateChange>,
+    pub new_peers: Vec<NewPeers>,
+}
+
+/// TCP and Unix sockets in `LISTEN`, and bound UDP sockets.
+fn is_listener(row: &ProcessInfo) -> bool {
+    row.state == "LISTEN" || row.protocol.starts_with("udp")
+}
+
+fn is_unix(row: &ProcessInfo) -> bool {
+    row.protocol.starts_with("unix")
+}
+
+/// Row IDs, which name the same socket in both snapshots. Hand-written
+/// snapshots may leave them out.
+fn ids(rows: &[ProcessInfo]) -> HashSet<&str> {
+    rows.iter()
+        .map(|row| row.id.as_str())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// What stays the same for a socket across restarts: row IDs include the
+/// PID and inode, which do not. For Unix sockets only a listener's path is
+/// such a key; unnamed sockets, and all clients of one path, would collapse
+/// into one, so they are matched by ID only.
+type ListenerKey = (String, Option<String>, Option<u16>);
+type ConnectionKey = (ListenerKey, Option<String>, Option<u16>);
+
+fn listener_key(row: &ProcessInfo) -> ListenerKey {
+    (
+        row.protocol.clone(),
+        row.local.address.clone(),
+        row.local.port,
+    )
+}
+
+fn connection_key(row: &ProcessInfo) -> ConnectionKey {
+    (
+        listener_key(row),
+        row.remote.address.clone(),
+        row.remote.port,
+    )
+}
+
+fn is_unnamed_unix(row: &ProcessInfo) -> bool {
+    is_unix(row) && row.local.address.is_none()
+}
+
+/// Internet and named Unix listeners by key; `SO_REUSEPORT` lets several
+/// sockets share one.
+fn listeners(rows: &[ProcessInfo]) -> BTreeMap<ListenerKey, Vec<&ProcessInfo>> {
+    let mut map: BTreeMap<ListenerKey, Vec<&ProcessInfo>> = BTreeMap::new();
+    for row in rows
+        .iter()
+        .filter(|row| is_listener(row) && !is_unnamed_unix(row))
+    {
+        map.entry(listener_key(row)).or_default().push(row);
+    }
+    map
+}
+
+/// Unnamed Unix listeners with an ID. Without one there is no telling
+/// whether they are new, so they are left out.
+fn unnamed_unix_listeners(rows: &[ProcessInfo]) -> impl Iterator<Item = &ProcessInfo> {
+    rows.iter()
+        .filter(|row| is_listener(row) && is_unnamed_unix(row) && !row.id.is_empty())
+}
+
+/// PIDs and names holding a listener, sorted by PID.
+fn holders(rows: &[&ProcessInfo]) -> (Vec<u32>, Vec<String>) {
+    let mut holders: BTreeMap<u32, &str> = BTreeMap::new();
+    for row in rows {
+        for (&pid, name) in row.pids.iter().zip(&row.process_names) {
+            holders.insert(pid, name);
+        }
+    }
+    (
+        holders.keys().copied().collect(),
+        holders.values().map(|name| name.to_string()).collect(),
+    )
+}
+
+/// Remote IP addresses (no ports, no wildcards) by process name. PIDs
+/// change across restarts; names usually do not.
+fn peers(rows: &[ProcessInfo]) -> HashMap<&str, (BTreeSet<u32>, BTreeSet<&str>)> {
+    let mut map: HashMap<&str, (BTreeSet<u32>, BTreeSet<&str>)> = HashMap::new();
+    for row in rows {
+        if is_listener(row) || is_unix(row) || row.process_name.is_empty() {
+            continue;
+        }
+        let Some(address) = row.remote.address.as_deref() else {
+            continue;
+        };
+        let entry = map.entry(row.process_name.as_str()).or_default();
+        entry.0.insert(row.pid);
+        entry.1.insert(address);
+    }
+    map
+}
+
+/// Compares two snapshots. Listeners are matched by address or path, so a
+/// restart shows up as a PID change; unnamed Unix listeners by ID. Connections are
+/// matched by ID, falling back to the addresses for Internet sockets whose
+/// ID changed.
+pub fn diff_snapshots(before: &Snapshot, after: &Snapshot) -> SnapshotDiff {
+    let old_ids = ids(&before.connections);
+    let new_ids = ids(&after.connections);
+    let old_listeners = listeners(&before.connections);
+    let new_listeners = listeners(&after.connections);
+
+    let mut diff = SnapshotDiff {
+        before: before.meta.clone(),
+        after: after.meta.clone(),
+        new_listeners: Vec::new(),
+        closed_listeners: Vec::new(),
+        pid_changes: Vec::new(),
+        state_changes: Vec::new(),
+        new_peers: Vec::new(),
+    };
+
+    for (key, rows) in &new_listeners {
+        let Some(old_rows) = old_listeners.get(key) else {
+            diff.new_listeners
+                .extend(rows.iter().map(|&row| row.clone()));
+            continue;
+        };
+        let (before_pids, before_names) = holders(old_rows);
+        let (after_pids, after_names) = holders(rows);
+        if before_pids != after_pids {
+            diff.pid_changes.push(PidChange {
+                protocol: key.0.clone(),
+                local: rows[0].local.clone(),
+                before_pids,
+                before_names,
+                after_pids,
+                after_names,
+            });
+        }
+    }
+    for (key, rows) in &old_listeners {
+        if !new_listeners.contains_key(key) {
+            diff.closed_listeners
+                .extend(rows.iter().map(|&row| row.clone()));
+        }
+    }
+
+    for row in unnamed_unix_listeners(&after.connections) {
+        if !old_ids.contains(row.id.as_str()) {
+            diff.new_listeners.push(row.clone());
+        }
+    }
+    for row in unnamed_unix_listeners(&before.connections) {
+        if !new_ids.contains(row.id.as_str()) {
+            diff.closed_listeners.push(row.clone());
+        }
+    }
+
+    let old_connections: Vec<&ProcessInfo> = before
+        .connections
+        .iter()
+        .filter(|row| !is_listener(row))
+        .collect();
+    let old_by_id: HashMap<&str, &str> = old_connections
+        .iter()
+        .filter(|row| !row.id.is_empty())
+        .map(|row| (row.id.as_str(), row.state.as_str()))
+        .collect();
+    let old_by_key: HashMap<ConnectionKey, &str> = old_connections
+        .iter()
+        .filter(|row| !is_unix(row))
+        .map(|row| (connection_key(row), row.state.as_str()))
+        .collect();
+    for row in after.connections.iter().filter(|row| !is_listener(row)) {
+        let from = match old_by_id.get(row.id.as_str()) {
+            Some(&from) => Some(from),
+            None if is_unix(row) => None,
+            None => old_by_key.get(&connection_key(row)).copied(),
+        };
+        match from {
+            Some(from) if from != row.state => diff.state_changes.push(StateChange {
+                connection: row.clone(),
+                from: from.to_string(),
+                to: row.state.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    let old_peers = peers(&before.connections);
+    let mut new_peers: Vec<NewPeers> = peers(&after.connections)
+        .into_iter()
+        .filter_map(|(name, (pids, addresses))| {
+            let known = old_peers.get(name).map(|(_, known)| known);
+            let added: Vec<String> = addresses
+                .into_iter()
+                .filter(|address| !known.is_some_and(|known| known.contains(address)))
+                .map(str::to_string)
+                .collect();
+            (!added.is_empty()).then(|| NewPeers {
+                process_name: name.to_string(),
+                pids: pids.into_iter().collect(),
+                peers: added,
+            })
+        })
+        .collect();
+    new_peers.sort_by(|a, b| a.process_name.cmp(&b.process_name));
+    diff.new_peers = new_peers;
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_info::connection_id;
+
+    fn snapshot(connections: Vec<ProcessInfo>) -> Snapshot {
+        Snapshot {
+            meta: SnapshotMeta {
+                hostname: None,
+                os: None,
+                kernel: None,
+                taken_at: 0,
+                tool_version: String::new(),
+                source: None,
+            },
+            connections,
+        }
+    }
+
+    fn endpoint(address: Option<&str>, port: Option<u16>) -> AddressPort {
+        AddressPort {
+            address: address.map(String::from),
+            port,
+            hostname: None,
+        }
+    }
+
+    fn row(
+        protocol: &str,
+        local: AddressPort,
+        remote: AddressPort,
+        state: &str,
+        (pid, name, inode): (u32, &str, u32),
+    ) -> ProcessInfo {
+        let mut row = ProcessInfo {
+            protocol: protocol.into(),
+            local,
+            remote,
+            state: state.into(),
+            pid,
+            process_name: name.into(),
+            pids: vec![pid],
+            process_names: vec![name.into()],
+            inode: Some(inode),
+            ..ProcessInfo::default()
+        };
+        row.id = connection_id(&row);
+        row
+    }
+
+    fn listener(port: u16, owner: (u32, &str, u32)) -> ProcessInfo {
+        let any = endpoint(None, None);
+        row("tcp", endpoint(None, Some(port)), any, "LISTEN", owner)
+    }
+
+    fn connection(remote: &str, state: &str, owner: (u32, &str, u32)) -> ProcessInfo {
+        row(
+            "tcp",
+            endpoint(Some("10.0.0.1"), Some(40000)),
+            endpoint(Some(remote), Some(443)),
+            state,
+            owner,
+        )
+    }
+
+    fn unix(path: Option<&str>, state: &str, owner: (u32, &str, u32)) -> ProcessInfo {
+        row(
+            "unix",
+            endpoint(path, None),
+            endpoint(None, None),
+            state,
+            owner,
+        )
+    }
+
+    #[test]
+    fn new_and_closed_listeners() {
+        let before = snapshot(vec![
+            listener(22, (1, "sshd", 10)),
+            listener(8080, (2, "old", 11)),
+        ]);
+        let after = snapshot(vec![
+            listener(22, (1, "sshd", 10)),
+            listener(9090, (3, "new", 12)),
+        ]);
+        let diff = diff_snapshots(&before, &after);
+
+        let ports = |rows: &[ProcessInfo]| rows.iter().map(|r| r.local.port).collect::<Vec<_>>();
+        assert_eq!(ports(&diff.new_listeners), [Some(9090)]);
+        assert_eq!(ports(&diff.closed_listeners), [Some(8080)]);
+        assert!(diff.pid_changes.is_empty());
+        assert!(diff.state_changes.is_empty());
+    }
+
+    #[test]
+    fn pid_change_on_the_same_port() {
+        let before = snapshot(vec![listener(443, (100, "nginx", 20))]);
+        let after = snapshot(vec![listener(443, (200, "nginx", 21))]);
+        let diff = diff_snapshots(&before, &after);
+
+        assert!(diff.new_listeners.is_empty());
+        assert!(diff.closed_listeners.is_empty());
+        assert_eq!(
+            diff.pid_changes,
+            [PidChange {
+                protocol: "tcp".into(),
+                local: endpoint(None, Some(443)),
+                before_pids: vec![100],
+                before_names: vec!["nginx".into()],
+                after_pids: vec![200],
+                after_names: vec!["nginx".into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn unix_listeners_by_path_or_id() {
+        // Two unnamed listeners share every address field.
+        let before = snapshot(vec![
+            unix(None, "LISTEN", (1, "a", 30)),
+            unix(None, "LISTEN", (2, "b", 31)),
+            unix(Some("/run/x.sock"), "LISTEN", (3, "x", 32)),
+        ]);
+        let after = snapshot(vec![
+            unix(None, "LISTEN", (1, "a", 30)),
+            unix(Some("/run/x.sock"), "LISTEN", (4, "x", 33)),
+            unix(Some("/run/y.sock"), "LISTEN", (5, "y", 34)),
+        ]);
+        let diff = diff_snapshots(&before, &after);
+
+        let owners = |rows: &[ProcessInfo]| rows.iter().map(|r| r.pid).collect::<Vec<_>>();
+        assert_eq!(owners(&diff.closed_listeners), [2]);
+        assert_eq!(owners(&diff.new_listeners), [5]);
+        assert_eq!(
+            diff.pid_changes,
+            [PidChange {
+                protocol: "unix".into(),
+                local: endpoint(Some("/run/x.sock"), None),
+                before_pids: vec![3],
+                before_names: vec!["x".into()],
+                after_pids: vec![4],
+                after_names: vec!["x".into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn unix_listeners_without_ids() {
+        let without_id = |row: ProcessInfo| ProcessInfo {
+            id: String::new(),
+            ..row
+        };
+        let before = snapshot(vec![
+            without_id(unix(None, "LISTEN", (1, "a", 30))),
+            without_id(unix(Some("/run/x.sock"), "LISTEN", (3, "x", 32))),
+        ]);
+        let after = snapshot(vec![
+            without_id(unix(None, "LISTEN", (1, "a", 30))),
+            without_id(unix(Some("/run/x.sock"), "LISTEN", (3, "x", 32))),
+        ]);
+        let diff = diff_snapshots(&before, &after);
+
+        assert!(diff.new_listeners.is_empty());
+        assert!(diff.closed_listeners.is_empty());
+        assert!(diff.pid_changes.is_empty());
+    }
+
+    #[test]
+    fn state_transitions() {
+        let before = snapshot(vec![
+            connection("192.0.2.1", "ESTABLISHED", (5, "curl", 40)),
+            connection("192.0.2.2", "ESTABLISHED", (6, "app", 41)),
+            unix(None, "ESTABLISHED", (7, "client", 42)),
+            unix(None, "ESTABLISHED", (8, "client", 43)),
+        ]);
+        let after = snapshot(vec![
+            // Same socket.
+            connection("192.0.2.1", "CLOSE_WAIT", (5, "curl", 40)),
+            // Same addresses, but the ID changed with the inode.
+            connection("192.0.2.2", "TIME_WAIT", (0, "", 0)),
+            // The other unnamed socket must not be mistaken for this one.
+            unix(None, "CLOSED", (9, "client", 44)),
+            unix(None, "ESTABLISHED", (8, "client", 43)),
+        ]);
+        let diff = diff_snapshots(&before, &after);
+
+        let changes: Vec<(Option<&str>, &str, &str)> = diff
+            .state_changes
+            .iter()
+            .map(|c| {
+                (
+                    c.connection.remote.address.as_deref(),
+                    c.from.as_str(),
+                    c.to.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (Some("192.0.2.1"), "ESTABLISHED", "CLOSE_WAIT"),
+                (Some("192.0.2.2"), "ESTABLISHED", "TIME_WAIT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn new_peers_by_process_name() {
+        let before = snapshot(vec![
+            connection("192.0.2.1", "ESTABLISHED", (5, "curl", 40)),
+            listener(80, (1, "nginx", 10)),
+        ]);
+        let after = snapshot(vec![
+            connection("192.0.2.1", "ESTABLISHED", (5, "curl", 40)),
+            connection("198.51.100.7", "ESTABLISHED", (6, "curl", 41)),
+            connection("203.0.113.9", "SYN_SENT", (7, "wget", 42)),
+            listener(80, (1, "nginx", 10)),
+        ]);
+        let diff = diff_snapshots(&before, &after);
+
+        assert_eq!(
+            diff.new_peers,
+            [
+                NewPeers {
+                    process_name: "curl".into(),
+                    pids: vec![5, 6],
+                    peers: vec!["198.51.100.7".into()],
+                },
+                NewPeers {
+                    process_name: "wget".into(),
+                    pids: vec![7],
+                    peers: vec!["203.0.113.9".into()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::process_info::ProcessInfo;
+
+struct Seen {
+    first_seen: u64,
+    state: String,
+    state_changed_at: u64,
+}
+
+/// Remembers when each connection ID was first seen and when its state last
+/// changed. Entries are dropped as soon as a sample no longer contains the
+/// connection, so a reused 4-tuple starts over with a fresh `first_seen`.
+#[derive(Default)]
+pub struct ConnectionTracker {
+    seen: Mutex<HashMap<String, Seen>>,
+}
+
+impl ConnectionTracker {
+    /// Fills `first_seen`, `age` and `state_changed_at` on a full sample.
+    pub fn annotate(&self, rows: &mut [ProcessInfo]) {
+        let now = now_ms();
+        let mut seen = self.seen.lock().unwrap();
+        let mut next = HashMap::with_capacity(rows.len());
+
+        for row in rows.iter_mut() {
+            let mut entry = seen.remove(&row.id).unwrap_or_else(|| Seen {
+                first_seen: now,
+                state: row.state.clone(),
+                state_changed_at: now,
+            });
+            if entry.state != row.state {
+                entry.state = row.state.clone();
+                entry.state_changed_at = now;
+            }
+
+            row.first_seen = Some(entry.first_seen);
+            row.age = Some(now.saturating_sub(entry.first_seen));
+            row.state_changed_at = Some(entry.state_changed_at);
+            next.insert(row.id.clone(), entry);
+        }
+
+        *seen = next;
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tauri::Emitter;
+
+use crate::changes::{diff_rows, METRICS_INTERVAL};
+use crate::sampler::Sampler;
+
+/// Emitted with a [`crate::changes::ConnectionDiff`] payload after every sample that changed
+/// something. The first event after subscribing carries every row in `added`.
+pub const DIFF_EVENT: &str = "connections-diff";
+/// Emitted with a [`crate::error::NetstatCatError`] when a sample fails.
+pub const ERROR_EVENT: &str = "connections-error";
+
+pub const DEFAULT_INTERVAL_MS: u64 = 2000;
+const MIN_INTERVAL_MS: u64 = 250;
+
+/// The live view's name among the [`Sampler`]'s consumers.
+const CONSUMER: &str = "watcher";
+
+/// Starts pushing diffs every `interval`. Subscribing again restarts with
+/// the new interval and a fresh baseline.
+pub fn subscribe(sampler: &Sampler, interval: Duration) {
+    let interval = interval.max(Duration::from_millis(MIN_INTERVAL_MS));
+    let mut previous = HashMap::new();
+    let mut sequence = 0;
+    let mut metrics_sent = Instant::now();
+    sampler.subscribe(CONSUMER, interval, ERROR_EVENT, move |app, rows| {
+        let with_metrics = metrics_sent.elapsed() >= METRICS_INTERVAL;
+        if with_metrics {
+            metrics_sent = Instant::now();
+        }
+        let diff = diff_rows(&mut previous, rows, sequence + 1, with_metrics);
+        if !diff.is_empty() {
+            sequence = diff.sequence;
+            let _ = app.emit(DIFF_EVENT, &diff);
+        }
+        Ok(())
+    });
+}
+
+pub fn unsubscribe(sampler: &Sampler) {
+    sampler.unsubscribe(CONSUMER);
+}
@@ -1,6 +1,7 @@
 import { useState, useEffect, useMemo, useCallback, useRef } from 'react'
 import { TableVirtuoso } from 'react-virtuoso'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { check } from '@tauri-apps/plugin-updater'
 import { init as initAptabase, trackEvent } from '@aptabase/web'
@@ -11,6 +12,7 @@ import logo from './assets/pure_cat_logo.png'
 initAptabase('A-US-4170254896')
 
 interface NetstatItem {
+  id: string
   protocol: string
   local: {
     address: string | null
@@ -19,17 +21,125 @@ interface NetstatItem {
   remote: {
     address: string | null
     port: number | null
+    // Reverse DNS name, once resolved
+    hostname?: string | null
   }
   state: string
   pid: number
   processName: string
+  pids: number[]
+  processNames: string[]
+  processState?:
+    | 'running'
+    | 'sleeping'
+    | 'diskSleep'
+    | 'idle'
+    | 'stopped'
+    | 'tracing'
+    | 'zombie'
+    | 'dead'
+    | 'unknown'
+    | null
   processPath?: string
   uid?: number | null
+  inode?: number | null
+  peer?: {
+    inode: number
+    pids: number[]
+    processNames: string[]
+  } | null
+  tcpInfo?: {
+    recvQ: number
+    sendQ: number
+    rttUs: number | null
+    rttvarUs: number | null
+    cwnd: number | null
+    ssthresh: number | null
+    retransmits: number | null
+    totalRetrans: number | null
+    lost: number | null
+    bytesAcked: number | null
+    bytesReceived: number | null
+    deliveryRate: number | null
+    pacingRate: number | null
+    congestion: string | null
+  } | null
+  firstSeen?: number | null
+  age?: number | null
+  stateChangedAt?: number | null
   fileDescriptor?: string
   fileType?: string
 }
 
-type ProtocolFilter = 'all' | 'tcp' | 'udp'
+interface ConnectionDiff {
+  sequence: number
+  added: NetstatItem[]
+  changed: NetstatItem[]
+  removed: string[]
+}
+
+// Shape of every error returned by a backend command (NetstatCatError).
+interface CommandError {
+  kind:
+    | 'socketEnumeration'
+    | 'processNotFound'
+    | 'connectionNotFound'
+    | 'permissionDenied'
+    | 'protected'
+    | 'signal'
+    | 'io'
+    | 'database'
+    | 'invalidFilter'
+    | 'invalidArgument'
+    | 'unsupported'
+    | 'cancelled'
+  message: string
+  errno: number | null
+  pid: number | null
+  span: { start: number; end: number } | null
+}
+
+interface KillReport {
+  pid: number
+  sent: string[]
+  exited: boolean
+  endedBy: string | null
+}
+
+interface SnapshotMeta {
+  hostname: string | null
+  os: string | null
+  kernel: string | null
+  takenAt: number
+  toolVersion: string
+  // Set for imported ss/netstat/lsof output
+  source: string | null
+}
+
+interface Snapshot {
+  meta: SnapshotMeta
+  connections: NetstatItem[]
+}
+
+type ExportFormat = 'csv' | 'json' | 'ndjson' | 'markdown' | 'html'
+
+const errorMessage = (err: unknown, fallback: string): string =>
+  (err as CommandError | undefined)?.message || fallback
+
+const applyDiff = (prevData: NetstatItem[], diff: ConnectionDiff): NetstatItem[] => {
+  const removed = new Set(diff.removed)
+  const changed = new Map(diff.changed.map((item) => [item.id, item]))
+  const next = prevData
+    .filter((item) => !removed.has(item.id))
+    .map((item) => {
+      const update = changed.get(item.id)
+      // Spread over the old row so client-side fields like processPath survive
+      return update ? { ...item, ...update } : item
+    })
+  return next.concat(diff.added)
+}
+
+type ProtocolFilter = 'all' | 'tcp' | 'udp' | 'unix'
 type IpVerFilter = 'all' | '4' | '6'
 type StateFilter = 'all' | 'listen' | 'established' | 'other'
 
@@ -38,6 +148,9 @@ function App() {
   const [loading, setLoading] = useState(false)
   const [error, setError] = useState<string | null>(null)
   const [autoRefresh, setAutoRefresh] = useState(false)
+  // Set while a loaded snapshot is shown instead of live data
+  const [snapshot, setSnapshot] = useState<SnapshotMeta | null>(null)
+  const [exportFormat, setExportFormat] = useState<ExportFormat>('csv')
   const [darkMode, setDarkMode] = useState(() => {
     return localStorage.getItem('theme') === 'dark'
   })
@@ -55,9 +168,13 @@ function App() {
 
   // Toast
   const [toast, setToast] = useState<{ message: string; type: 'success' | 'error' } | null>(null)
+  // Stays up until dismissed: toasts of the kill itself would hide it
+  const [auditError, setAuditError] = useState<string | null>(null)
   const toastTimer = useRef<ReturnType<typeof setTimeout>>(undefined)
 
   const isMacOS = navigator.platform.toUpperCase().includes('MAC')
+  // Suspend/resume needs SIGSTOP/SIGCONT, which Windows does not have.
+  const isWindows = navigator.platform.toUpperCase().startsWith('WIN')
 
   const showToast = useCallback((message: string, type: 'success' | 'error' = 'success') => {
     clearTimeout(toastTimer.current)
@@ -119,6 +236,9 @@ function App() {
   }
 
   const handleProcessHover = async (_index: number, item: NetstatItem) => {
+    // PIDs in a snapshot may belong to another machine
+    if (snapshot) return
+
     // 1. Check if path is already in item (fastest)
     if (item.processPath !== undefined) {
       console.log('Path already in item:', item.processPath)
@@ -158,29 +278,112 @@ function App() {
     })
   }
 
-  const handleKillProcess = async (pid: number, processName: string) => {
+  const handleKillProcess = async (pid: number, processName: string, force = false) => {
     try {
-      await invoke('kill_process', { pid })
-      showToast(`Process "${processName}" (PID: ${pid}) killed`)
+      // SIGTERM first; the backend follows up with SIGKILL after a grace period.
+      const report = await invoke<KillReport>('kill_process', { pid, options: { force } })
+      if (!report.exited) {
+        showToast(`Process "${processName}" (PID: ${pid}) is still running`, 'error')
+      } else if (report.endedBy === 'SIGKILL' && report.sent.length > 1) {
+        showToast(`Process "${processName}" (PID: ${pid}) force killed`)
+      } else {
+        showToast(`Process "${processName}" (PID: ${pid}) killed`)
+      }
       await fetchData()
-    } catch (err: any) {
-      showToast(err.toString?.() || `Failed to kill process ${pid}`, 'error')
+    } catch (err) {
+      if (
+        !force &&
+        (err as CommandError | undefined)?.kind === 'protected' &&
+        window.confirm(`${errorMessage(err, '')}\n\nKill "${processName}" anyway?`)
+      ) {
+        return handleKillProcess(pid, processName, true)
+      }
+      showToast(errorMessage(err, `Failed to kill process ${pid}`), 'error')
+    }
+  }
+
+  const handleToggleSuspend = async (item: NetstatItem) => {
+    const stopped = item.processState === 'stopped'
+    try {
+      await invoke(stopped ? 'resume_process' : 'suspend_process', { pid: item.pid })
+      showToast(
+        `Process "${item.processName}" (PID: ${item.pid}) ${stopped ? 'resumed' : 'suspended'}`
+      )
+      await fetchData()
+    } catch (err) {
+      showToast(errorMessage(err, `Failed to suspend process ${item.pid}`), 'error')
+    }
+  }
+
+  const handleSaveSnapshot = async () => {
+    try {
+      const path = await invoke<string>('save_snapshot')
+      showToast(`Snapshot saved to ${path}`)
+    } catch (err) {
+      showToast(errorMessage(err, 'Failed to save snapshot'), 'error')
+    }
+  }
+
+  const showSnapshot = (loaded: Snapshot) => {
+    setAutoRefresh(false)
+    setSnapshot(loaded.meta)
+    setData(loaded.connections)
+    setError(null)
+  }
+
+  const handleOpenSnapshot = async () => {
+    const path = window.prompt('Path of the snapshot to open')
+    if (!path) return
+    try {
+      showSnapshot(await invoke<Snapshot>('load_snapshot', { path }))
+    } catch (err) {
+      showToast(errorMessage(err, 'Failed to open snapshot'), 'error')
     }
   }
 
+  const handleImport = async () => {
+    const path = window.prompt('Path of a saved ss -tanp, netstat -anp or lsof -i -n -P output')
+    if (!path) return
+    try {
+      showSnapshot(await invoke<Snapshot>('import_connections', { path }))
+    } catch (err) {
+      showToast(errorMessage(err, 'Failed to import connections'), 'error')
+    }
+  }
+
+  const handleExport = async () => {
+    try {
+      // Exactly the rows on screen, live or from a snapshot
+      const path = await invoke<string>('export_connections', {
+        format: exportFormat,
+        connections: filteredData,
+      })
+      showToast(`Exported ${filteredData.length} rows to ${path}`)
+    } catch (err) {
+      showToast(errorMessage(err, 'Failed to export connections'), 'error')
+    }
+  }
+
+  const handleLeaveSnapshot = () => {
+    setSnapshot(null)
+    setData([])
+  }
+
   const fetchData = async () => {
     // ... (fetch logic remains same)
     // If we are already loading, don't stack requests (prevents lag if request takes > 2s)
-    if (loading) return
+    if (loading || snapshot) return
 
     setLoading(true)
     try {
       const result = await invoke<NetstatItem[]>('get_process_info_list')
       setData(result)
       setError(null)
-    } catch (err: any) {
+    } catch (err) {
+      // Superseded by a newer request; its result will arrive instead
+      if ((err as CommandError | undefined)?.kind === 'cancelled') return
       console.error(err)
-      setError(err.message || 'Failed to fetch data')
+      setError(errorMessage(err, 'Failed to fetch data'))
     } finally {
       setLoading(false)
     }
@@ -189,17 +392,59 @@ function App() {
   useEffect(() => {
     getCurrentWindow().show()
     trackEvent('main-window-appear')
-    fetchData()
   }, [])
 
+  // Live data on start and again after leaving a snapshot
+  useEffect(() => {
+    if (!snapshot) fetchData()
+  }, [snapshot])
+
   useEffect(() => {
-    let interval: ReturnType<typeof setInterval>
-    if (autoRefresh) {
-      interval = setInterval(() => {
-        fetchData()
-      }, 2000)
+    const unlisten = listen<CommandError>('audit-error', ({ payload }) =>
+      setAuditError(`Kill audit log not written: ${payload.message}`)
+    )
+    return () => {
+      unlisten.then((unlisten) => unlisten())
+    }
+  }, [])
+
+  useEffect(() => {
+    if (!autoRefresh) return
+
+    // The backend samples on its own thread and pushes only what changed
+    let lastSequence = 0
+    const subscribe = () => {
+      lastSequence = 0
+      invoke('subscribe_connections', { intervalMs: 2000 }).catch((err) =>
+        setError(errorMessage(err, 'Failed to subscribe to updates'))
+      )
+    }
+    const unlistenDiff = listen<ConnectionDiff>('connections-diff', ({ payload }) => {
+      if (payload.sequence === 1) {
+        setData(payload.added)
+      } else if (payload.sequence === lastSequence + 1) {
+        setData((prevData) => applyDiff(prevData, payload))
+      } else {
+        // Missed an event; start over from a fresh baseline
+        subscribe()
+        return
+      }
+      lastSequence = payload.sequence
+      setError(null)
+    })
+    const unlistenError = listen<CommandError>('connections-error', ({ payload }) =>
+      setError(payload.message)
+    )
+
+    Promise.all([unlistenDiff, unlistenError]).then(subscribe)
+
+    return () => {
+      // Don't keep the backend busy with a scan nobody will read
+      invoke('cancel_scan')
+      unlistenDiff.then((unlisten) => unlisten())
+      unlistenError.then((unlisten) => unlisten())
+      invoke('unsubscribe_connections')
     }
-    return () => clearInterval(interval)
   }, [autoRefresh])
 
   const filteredData = useMemo(() => {
@@ -212,6 +457,7 @@ function App() {
 
       // 2. IP Version Filter
       if (filterIpVer !== 'all') {
+        if (item.protocol.startsWith('unix')) return false
         const isV6 = item.protocol.endsWith('6')
         if (filterIpVer === '4' && isV6) return false
         if (filterIpVer === '6' && !isV6) return false
@@ -498,6 +744,7 @@ function App() {
                   <input
                     type="checkbox"
                     checked={autoRefresh}
+                    disabled={snapshot !== null}
                     onChange={(e) => setAutoRefresh(e.target.checked)}
                     className="hidden"
                   />
@@ -512,7 +759,7 @@ function App() {
                 </label>
                 <button
                   onClick={fetchData}
-                  disabled={loading}
+                  disabled={loading || snapshot !== null}
                   className="bg-white dark:bg-gray-700 hover:bg-gray-50 dark:hover:bg-gray-600 text-gray-700 dark:text-gray-200 px-3 py-1.5 disabled:opacity-50 transition-colors flex items-center justify-center"
                   title="Refresh Now"
                 >
@@ -531,6 +778,58 @@ function App() {
                   </svg>
                 </button>
               </div>
+              <div
+                className="inline-flex rounded-md shadow-sm border border-gray-300 dark:border-gray-600 overflow-hidden"
+                role="group"
+              >
+                <button
+                  onClick={handleSaveSnapshot}
+                  disabled={snapshot !== null}
+                  className="bg-white dark:bg-gray-700 hover:bg-gray-50 dark:hover:bg-gray-600 text-xs font-medium text-gray-600 dark:text-gray-300 px-3 py-1.5 disabled:opacity-50 transition-colors border-r border-gray-300 dark:border-gray-600"
+                  title="Save the current connections to a snapshot file"
+                >
+                  Save
+                </button>
+                <button
+                  onClick={handleOpenSnapshot}
+                  className="bg-white dark:bg-gray-700 hover:bg-gray-50 dark:hover:bg-gray-600 text-xs font-medium text-gray-600 dark:text-gray-300 px-3 py-1.5 transition-colors border-r border-gray-300 dark:border-gray-600"
+                  title="Open a snapshot file (read-only)"
+                >
+                  Open
+                </button>
+                <button
+                  onClick={handleImport}
+                  className="bg-white dark:bg-gray-700 hover:bg-gray-50 dark:hover:bg-gray-600 text-xs font-medium text-gray-600 dark:text-gray-300 px-3 py-1.5 transition-colors"
+                  title="Import ss, netstat or lsof output from another machine (read-only)"
+                >
+                  Import
+                </button>
+              </div>
+              <div
+                className="inline-flex rounded-md shadow-sm border border-gray-300 dark:border-gray-600 overflow-hidden"
+                role="group"
+              >
+                <select
+                  value={exportFormat}
+                  onChange={(e) => setExportFormat(e.target.value as ExportFormat)}
+                  className="bg-white dark:bg-gray-700 text-xs font-medium text-gray-600 dark:text-gray-300 px-2 py-1.5 border-r border-gray-300 dark:border-gray-600 focus:outline-none"
+                  title="Export format"
+                >
+                  <option value="csv">CSV</option>
+                  <option value="json">JSON</option>
+                  <option value="ndjson">NDJSON</option>
+                  <option value="markdown">Markdown</option>
+                  <option value="html">HTML</option>
+                </select>
+                <button
+                  onClick={handleExport}
+                  disabled={filteredData.length === 0}
+                  className="bg-white dark:bg-gray-700 hover:bg-gray-50 dark:hover:bg-gray-600 text-xs font-medium text-gray-600 dark:text-gray-300 px-3 py-1.5 disabled:opacity-50 transition-colors"
+                  title="Export the rows shown to a file in the Downloads folder"
+                >
+                  Export
+                </button>
+              </div>
             </div>
           </div>
 
@@ -542,7 +841,7 @@ function App() {
                 Protocol
               </span>
               <div className="flex rounded-md shadow-sm" role="group">
-                {(['all', 'tcp', 'udp'] as const).map((p) => (
+                {(['all', 'tcp', 'udp', 'unix'] as const).map((p) => (
                   <button
                     key={p}
                     onClick={() => setFilterProtocol(p)}
@@ -624,6 +923,42 @@ function App() {
           </div>
         </div>
 
+        {snapshot && (
+          <div className="max-w-full px-4 mx-auto mt-2 bg-amber-50 dark:bg-amber-900/30 border border-amber-300 dark:border-amber-800 text-amber-800 dark:text-amber-300 py-2 rounded text-sm flex items-center gap-3 transition-colors">
+            {snapshot.source ? (
+              <span className="flex-grow">
+                Read-only import of <strong>{snapshot.source}</strong> output saved{' '}
+                {new Date(snapshot.takenAt).toLocaleString()}
+              </span>
+            ) : (
+              <span className="flex-grow">
+                Read-only snapshot of <strong>{snapshot.hostname || 'unknown host'}</strong> taken{' '}
+                {new Date(snapshot.takenAt).toLocaleString()}
+                {snapshot.kernel && ` · kernel ${snapshot.kernel}`} · netstat-cat{' '}
+                {snapshot.toolVersion}
+              </span>
+            )}
+            <button
+              onClick={handleLeaveSnapshot}
+              className="px-3 py-1 rounded bg-amber-600 hover:bg-amber-700 text-white text-xs font-medium transition-colors"
+            >
+              Back to live
+            </button>
+          </div>
+        )}
+
+        {auditError && (
+          <div className="max-w-full px-4 mx-auto mt-2 bg-red-100 dark:bg-red-900/30 border border-red-400 dark:border-red-800 text-red-700 dark:text-red-300 py-2 rounded text-sm flex items-center gap-3 transition-colors">
+            <span className="flex-grow">{auditError}</span>
+            <button
+              onClick={() => setAuditError(null)}
+              className="px-3 py-1 rounded bg-red-600 hover:bg-red-700 text-white text-xs font-medium transition-colors"
+            >
+              Dismiss
+            </button>
+          </div>
+        )}
+
         {error && (
           <div className="max-w-full px-4 mx-auto mt-2 bg-red-100 dark:bg-red-900/30 border border-red-400 dark:border-red-800 text-red-700 dark:text-red-300 px-4 py-2 rounded text-sm transition-colors">
             {error}
@@ -670,7 +1005,7 @@ function App() {
                 <th className="px-5 py-3 border-b-2 border-gray-200 dark:border-gray-600 text-left text-xs font-bold text-gray-600 dark:text-gray-300 uppercase tracking-wider w-full whitespace-nowrap">
                   Process
                 </th>
-                <th className="px-2 py-3 border-b-2 border-gray-200 dark:border-gray-600 w-10 sticky right-0 bg-gray-50 dark:bg-gray-700"></th>
+                <th className="px-2 py-3 border-b-2 border-gray-200 dark:border-gray-600 w-20 sticky right-0 bg-gray-50 dark:bg-gray-700"></th>
               </tr>
             )}
             itemContent={(_index, item) => (
@@ -689,13 +1024,24 @@ function App() {
                   </span>
                 </td>
                 <td className="px-5 py-2 border-b border-gray-200 dark:border-gray-700 text-sm font-mono text-gray-700 dark:text-gray-300 align-top w-64 whitespace-nowrap">
-                  {item.local.address || (item.protocol.includes('6') ? '[::]' : '0.0.0.0')}:
-                  {item.local.port}
+                  {item.protocol.startsWith('unix')
+                    ? item.local.address || '(unnamed)'
+                    : `${item.local.address || (item.protocol.includes('6') ? '[::]' : '0.0.0.0')}:${item.local.port ?? ''}`}
                 </td>
                 <td className="px-5 py-2 border-b border-gray-200 dark:border-gray-700 text-sm font-mono text-gray-700 dark:text-gray-300 align-top w-64 whitespace-nowrap">
-                  {item.remote.address || item.remote.port
-                    ? `${item.remote.address || (item.protocol.includes('6') ? '[::]' : '0.0.0.0')}:${item.remote.port}`
-                    : '-'}
+                  {item.protocol.startsWith('unix')
+                    ? item.remote.address || (item.peer ? `inode ${item.peer.inode}` : '-')
+                    : item.remote.address || item.remote.port
+                      ? `${item.remote.address || (item.protocol.includes('6') ? '[::]' : '0.0.0.0')}:${item.remote.port}`
+                      : '-'}
+                  {item.remote.hostname && (
+                    <div
+                      className="text-xs text-gray-400 dark:text-gray-500 truncate max-w-[16rem]"
+                      title={item.remote.hostname}
+                    >
+                      {item.remote.hostname}
+                    </div>
+                  )}
                 </td>
                 <td className="px-5 py-2 border-b border-gray-200 dark:border-gray-700 text-sm align-top w-32 whitespace-nowrap">
                   {item.state ? (
@@ -729,22 +1075,47 @@ function App() {
                   onMouseEnter={() => handleProcessHover(_index, item)}
                 >
                   {item.processName || '-'}
+                  {item.processState === 'stopped' && (
+                    <span className="ml-2 px-1.5 py-0.5 rounded text-xs font-normal bg-amber-100 text-amber-800 dark:bg-amber-900/50 dark:text-amber-300">
+                      stopped
+                    </span>
+                  )}
                 </td>
-                <td className="px-2 py-2 border-b border-gray-200 dark:border-gray-700 align-top w-10 sticky right-0 bg-white dark:bg-gray-800">
-                  <button
-                    onClick={() => handleKillProcess(item.pid, item.processName)}
-                    className="opacity-0 group-hover:opacity-100 p-1 rounded hover:bg-red-100 dark:hover:bg-red-900/50 text-gray-400 hover:text-red-600 dark:hover:text-red-400 transition-all"
-                    title={`Kill process ${item.processName} (PID: ${item.pid})`}
-                  >
-                    <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                      <path
-                        strokeLinecap="round"
-                        strokeLinejoin="round"
-                        strokeWidth="2"
-                        d="M6 18L18 6M6 6l12 12"
-                      />
-                    </svg>
-                  </button>
+                <td className="px-2 py-2 border-b border-gray-200 dark:border-gray-700 align-top w-20 sticky right-0 bg-white dark:bg-gray-800 whitespace-nowrap">
+                  {!snapshot && (
+                    <>
+                      {!isWindows && (
+                        <button
+                          onClick={() => handleToggleSuspend(item)}
+                          className="opacity-0 group-hover:opacity-100 p-1 rounded hover:bg-amber-100 dark:hover:bg-amber-900/50 text-gray-400 hover:text-amber-600 dark:hover:text-amber-400 transition-all"
+                          title={`${item.processState === 'stopped' ? 'Resume' : 'Suspend'} process ${item.processName} (PID: ${item.pid})`}
+                        >
+                          <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                            <path
+                              strokeLinecap="round"
+                              strokeLinejoin="round"
+                              strokeWidth="2"
+                              d={item.processState === 'stopped' ? 'M6 4l14 8-14 8V4z' : 'M10 5v14M14 5v14'}
+                            />
+                          </svg>
+                        </button>
+                      )}
+                      <button
+                        onClick={() => handleKillProcess(item.pid, item.processName)}
+                        className="opacity-0 group-hover:opacity-100 p-1 rounded hover:bg-red-100 dark:hover:bg-red-900/50 text-gray-400 hover:text-red-600 dark:hover:text-red-400 transition-all"
+                        title={`Kill process ${item.processName} (PID: ${item.pid})`}
+                      >
+                        <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                          <path
+                            strokeLinecap="round"
+                            strokeLinejoin="round"
+                            strokeWidth="2"
+                            d="M6 18L18 6M6 6l12 12"
+                          />
+                        </svg>
+                      </button>
+                    </>
+                  )}
                 </td>
               </>
             )}